@@ -1,21 +1,76 @@
+use crate::error::{MirrorError, Result};
 use crate::types::Mirror;
 use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
+use std::path::PathBuf;
 use std::sync::OnceLock;
 
 // Include the JSON file at compile time
 const MIRRORS_JSON: &str = include_str!("../assets/mirrors.json");
 
+/// 内置默认列表的来源：要么是编译进二进制的 `assets/mirrors.json`，要么是
+/// 本地 `config_dir/mirrors.json` 对它的整体覆盖——这是早于 `mirrors.toml`
+/// (按条目覆盖) 的历史遗留机制，一旦存在就会整体取代编译进二进制的默认列表。
+/// 记下具体来源是为了让 `cmirror status` 能准确标注 provenance，而不是让一份
+/// 已经被本地文件整体替换掉的列表还显示成 "builtin"。
+struct BuiltinMirrors {
+    data: HashMap<String, Vec<Mirror>>,
+    origin: Origin,
+}
+
 // Global cache for parsed mirrors
-static MIRRORS_CACHE: OnceLock<HashMap<String, Vec<Mirror>>> = OnceLock::new();
-
-/// Retrieve the list of mirror candidates for a given tool
-/// Strategy:
-/// 1. Try to load from User Config (~/.config/cmirror/mirrors.json)
-/// 2. Fallback to built-in assets/mirrors.json
-pub fn get_candidates(tool_name: &str) -> Vec<Mirror> {
-    let mirrors = MIRRORS_CACHE.get_or_init(|| {
+static MIRRORS_CACHE: OnceLock<BuiltinMirrors> = OnceLock::new();
+
+/// 用户自定义镜像仓库文件，按工具名分组持久化到 ~/.config/cmirror/mirrors.toml
+type UserMirrors = HashMap<String, Vec<Mirror>>;
+
+/// 用户自定义镜像仓库文件的路径
+fn user_mirrors_path() -> Option<PathBuf> {
+    ProjectDirs::from("", "", "cmirror").map(|dirs| dirs.config_dir().join("mirrors.toml"))
+}
+
+/// 读取用户自定义镜像仓库文件，不存在则返回空集合
+fn load_user_mirrors() -> Result<UserMirrors> {
+    let Some(path) = user_mirrors_path() else {
+        return Ok(UserMirrors::new());
+    };
+    if !path.exists() {
+        return Ok(UserMirrors::new());
+    }
+    let content = fs::read_to_string(&path)?;
+    Ok(toml::from_str(&content)?)
+}
+
+/// 写回用户自定义镜像仓库文件
+fn save_user_mirrors(data: &UserMirrors) -> Result<()> {
+    let path = user_mirrors_path().ok_or_else(|| {
+        MirrorError::Custom("Could not determine user config directory".to_string())
+    })?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let content = toml::to_string_pretty(data)?;
+    fs::write(&path, content)?;
+    Ok(())
+}
+
+/// 粗略校验一个镜像 URL 是否可用：必须是 http(s) 地址
+fn validate_url(url: &str) -> Result<()> {
+    if url.starts_with("http://") || url.starts_with("https://") {
+        Ok(())
+    } else {
+        Err(MirrorError::Custom(format!(
+            "Invalid mirror URL '{}': must start with http:// or https://",
+            url
+        )))
+    }
+}
+
+/// 内置默认列表 (assets/mirrors.json，或本地 config_dir/mirrors.json 的整体覆盖)
+fn load_builtin_mirrors() -> &'static BuiltinMirrors {
+    MIRRORS_CACHE.get_or_init(|| {
         // 1. Try local config
         if let Some(proj_dirs) = ProjectDirs::from("", "", "cmirror") {
             let config_path = proj_dirs.config_dir().join("mirrors.json");
@@ -23,16 +78,359 @@ pub fn get_candidates(tool_name: &str) -> Vec<Mirror> {
                 if let Ok(content) = fs::read_to_string(&config_path) {
                     if let Ok(parsed) = serde_json::from_str(&content) {
                         println!("Loaded mirrors from local config: {:?}", config_path);
-                        return parsed;
+                        return BuiltinMirrors {
+                            data: parsed,
+                            origin: Origin::LocalOverride,
+                        };
                     }
                 }
             }
         }
 
         // 2. Fallback
-        serde_json::from_str(MIRRORS_JSON)
-            .expect("Failed to parse assets/mirrors.json. This is a compile-time error.")
-    });
+        let data = serde_json::from_str(MIRRORS_JSON)
+            .expect("Failed to parse assets/mirrors.json. This is a compile-time error.");
+        BuiltinMirrors {
+            data,
+            origin: Origin::Builtin,
+        }
+    })
+}
+
+/// 标注一条镜像候选的来源，供 `cmirror status` 之类的命令展示
+/// "这个值是从哪里来的" (环境变量覆盖 / 用户配置 / 本地整体覆盖 / 内置默认)。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Origin {
+    Env,
+    User,
+    /// 来自本地 `config_dir/mirrors.json` 对编译进二进制的默认列表的整体
+    /// 覆盖——早于 `mirrors.toml` 的历史遗留机制，优先级低于 `mirrors.toml`
+    /// 里按条目的自定义，但高于编译进二进制的 `assets/mirrors.json`。
+    LocalOverride,
+    Builtin,
+}
+
+impl Origin {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Origin::Env => "env",
+            Origin::User => "user-config",
+            Origin::LocalOverride => "local-override",
+            Origin::Builtin => "builtin",
+        }
+    }
+}
+
+/// 一条镜像候选及其来源
+#[derive(Debug, Clone, Serialize)]
+pub struct MirrorWithOrigin {
+    pub mirror: Mirror,
+    pub origin: Origin,
+}
+
+/// 分层配置解析入口：环境变量 > 用户配置 > 内置默认。
+pub struct Config;
+
+impl Config {
+    /// 按优先级合并某个工具的镜像候选列表，并标注每一条的来源：
+    /// 1. 内置默认 (`assets/mirrors.json`)，如果本地存在
+    ///    `~/.config/cmirror/mirrors.json` 则被它整体覆盖 (标注为
+    ///    `Origin::LocalOverride` 而不是 `Origin::Builtin`)
+    /// 2. 用户自定义 (`~/.config/cmirror/mirrors.toml`)：按 `name` 合并——
+    ///    同名条目整体替换上面那一层的条目 (而不仅仅替换 url)，否则追加
+    /// 3. 环境变量 `CMIRROR_<TOOL>_URL`：如果设置，强制插入一条名为 "Env" 的
+    ///    最高优先级候选，用来临时覆盖而不需要改动任何配置文件
+    ///
+    /// 用户配置文件存在但解析失败时返回 `Err`，而不是静默退回内置列表——
+    /// 这样用户才能发现自己的 mirrors.toml 写错了，而不是疑惑镜像为什么没生效。
+    pub fn get(tool_name: &str) -> Result<Vec<MirrorWithOrigin>> {
+        let builtin_source = load_builtin_mirrors();
+        let builtins = builtin_source
+            .data
+            .get(tool_name)
+            .cloned()
+            .unwrap_or_default();
+
+        let user_mirrors = load_user_mirrors()?;
+        let user_entries = user_mirrors
+            .get(tool_name)
+            .cloned()
+            .unwrap_or_default();
+
+        let env_key = format!("CMIRROR_{}_URL", tool_name.to_uppercase());
+        let env_override = std::env::var(&env_key).ok();
+
+        Ok(merge_candidates(
+            &builtins,
+            builtin_source.origin,
+            &user_entries,
+            env_override.as_deref(),
+        ))
+    }
+}
+
+/// 纯函数版本的三层优先级合并逻辑，从 `Config::get` 中拆出来，这样可以在不
+/// 依赖真实文件系统/环境变量的前提下单独测试这段合并/来源标注规则。
+/// `builtin_origin` 是 `builtins` 这批条目的来源标注——通常是
+/// `Origin::Builtin`，但如果它们其实是本地 `mirrors.json` 整体覆盖的结果，
+/// 调用方应传入 `Origin::LocalOverride`。
+fn merge_candidates(
+    builtins: &[Mirror],
+    builtin_origin: Origin,
+    user_entries: &[Mirror],
+    env_override: Option<&str>,
+) -> Vec<MirrorWithOrigin> {
+    let mut candidates: Vec<MirrorWithOrigin> = builtins
+        .iter()
+        .cloned()
+        .map(|mirror| MirrorWithOrigin {
+            mirror,
+            origin: builtin_origin,
+        })
+        .collect();
+
+    for entry in user_entries {
+        if let Some(existing) = candidates
+            .iter_mut()
+            .find(|c| c.mirror.name.eq_ignore_ascii_case(&entry.name))
+        {
+            existing.mirror = entry.clone();
+            existing.origin = Origin::User;
+        } else {
+            candidates.push(MirrorWithOrigin {
+                mirror: entry.clone(),
+                origin: Origin::User,
+            });
+        }
+    }
+
+    if let Some(url) = env_override {
+        if !url.is_empty() {
+            candidates.insert(
+                0,
+                MirrorWithOrigin {
+                    mirror: Mirror::new("Env", url),
+                    origin: Origin::Env,
+                },
+            );
+        }
+    }
+
+    candidates
+}
+
+/// Retrieve the list of mirror candidates for a given tool (without origin info).
+/// Thin wrapper around `Config::get` kept for the common case where managers just
+/// need the plain `Vec<Mirror>` to benchmark or search by name.
+pub fn get_candidates(tool_name: &str) -> Result<Vec<Mirror>> {
+    Ok(Config::get(tool_name)?
+        .into_iter()
+        .map(|entry| entry.mirror)
+        .collect())
+}
+
+/// 新增或覆盖一个工具的用户自定义镜像源，写回 mirrors.toml
+pub fn add_user_mirror(tool: &str, mirror: Mirror) -> Result<()> {
+    validate_url(&mirror.url)?;
+
+    let mut data = load_user_mirrors()?;
+    let entries = data.entry(tool.to_string()).or_default();
+
+    if let Some(existing) = entries
+        .iter_mut()
+        .find(|m| m.name.eq_ignore_ascii_case(&mirror.name))
+    {
+        existing.url = mirror.url;
+    } else {
+        entries.push(mirror);
+    }
+
+    save_user_mirrors(&data)
+}
+
+/// 移除一个工具下的用户自定义镜像源
+pub fn remove_user_mirror(tool: &str, name: &str) -> Result<()> {
+    let mut data = load_user_mirrors()?;
+    let entries = data.entry(tool.to_string()).or_default();
+
+    let before = entries.len();
+    entries.retain(|m| !m.name.eq_ignore_ascii_case(name));
+
+    if entries.len() == before {
+        return Err(MirrorError::Custom(format!(
+            "No user-defined mirror named '{}' for tool '{}'",
+            name, tool
+        )));
+    }
+
+    save_user_mirrors(&data)
+}
+
+/// 重命名/更新一个工具下的用户自定义镜像源 (名称和/或 URL)
+pub fn rename_user_mirror(
+    tool: &str,
+    name: &str,
+    new_name: Option<&str>,
+    new_url: Option<&str>,
+) -> Result<()> {
+    if let Some(url) = new_url {
+        validate_url(url)?;
+    }
+
+    let mut data = load_user_mirrors()?;
+    let entries = data.entry(tool.to_string()).or_default();
+
+    let entry = entries
+        .iter_mut()
+        .find(|m| m.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            MirrorError::Custom(format!(
+                "No user-defined mirror named '{}' for tool '{}'",
+                name, tool
+            ))
+        })?;
+
+    if let Some(new_name) = new_name {
+        entry.name = new_name.to_string();
+    }
+    if let Some(url) = new_url {
+        entry.url = url.to_string();
+    }
+
+    save_user_mirrors(&data)
+}
+
+/// `[network]` 配置段: 供受限网络环境 (代理、私有 CA) 下的测速客户端使用
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct NetworkConfig {
+    /// 显式代理地址 (如 "http://127.0.0.1:7890")。留空则沿用
+    /// reqwest 默认行为，即读取 HTTP_PROXY/HTTPS_PROXY/NO_PROXY 环境变量。
+    pub proxy: Option<String>,
+    /// 额外信任的 CA 证书 (PEM 格式) 路径，用于自签名/私有根证书的镜像
+    pub ca_cert: Option<PathBuf>,
+    /// 跳过证书校验 (仅用于临时调试，生产环境不建议开启)
+    pub danger_accept_invalid_certs: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CmirrorConfig {
+    #[serde(default)]
+    network: NetworkConfig,
+}
+
+/// 主配置文件路径: ~/.config/cmirror/config.toml
+fn main_config_path() -> Option<PathBuf> {
+    ProjectDirs::from("", "", "cmirror").map(|dirs| dirs.config_dir().join("config.toml"))
+}
+
+/// 读取 `[network]` 配置段，文件不存在或解析失败时返回空配置 (即沿用默认行为)
+pub fn load_network_config() -> NetworkConfig {
+    let Some(path) = main_config_path() else {
+        return NetworkConfig::default();
+    };
+    if !path.exists() {
+        return NetworkConfig::default();
+    }
+    let Ok(content) = fs::read_to_string(&path) else {
+        return NetworkConfig::default();
+    };
+    toml::from_str::<CmirrorConfig>(&content)
+        .map(|c| c.network)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_candidates_builtin_only() {
+        let builtins = vec![Mirror::new("Official", "https://official.example.com")];
+        let result = merge_candidates(&builtins, Origin::Builtin, &[], None);
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].origin, Origin::Builtin);
+        assert_eq!(result[0].origin.label(), "builtin");
+        assert_eq!(result[0].mirror.name, "Official");
+    }
+
+    #[test]
+    fn test_merge_candidates_user_entry_overrides_builtin_by_name() {
+        let builtins = vec![Mirror::new("Official", "https://official.example.com")];
+        let user = vec![Mirror::new("Official", "https://user-override.example.com")];
+        let result = merge_candidates(&builtins, Origin::Builtin, &user, None);
+
+        // Same name (case-insensitively) replaces the builtin entry in place
+        // rather than being appended as a second candidate.
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].origin, Origin::User);
+        assert_eq!(result[0].mirror.url, "https://user-override.example.com");
+    }
+
+    #[test]
+    fn test_merge_candidates_user_entry_with_new_name_is_appended() {
+        let builtins = vec![Mirror::new("Official", "https://official.example.com")];
+        let user = vec![Mirror::new("MyMirror", "https://mine.example.com")];
+        let result = merge_candidates(&builtins, Origin::Builtin, &user, None);
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].origin, Origin::Builtin);
+        assert_eq!(result[1].origin, Origin::User);
+        assert_eq!(result[1].mirror.name, "MyMirror");
+    }
+
+    #[test]
+    fn test_merge_candidates_env_override_wins_and_is_listed_first() {
+        let builtins = vec![Mirror::new("Official", "https://official.example.com")];
+        let user = vec![Mirror::new("MyMirror", "https://mine.example.com")];
+        let result = merge_candidates(
+            &builtins,
+            Origin::Builtin,
+            &user,
+            Some("https://env-override.example.com"),
+        );
+
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0].origin, Origin::Env);
+        assert_eq!(result[0].origin.label(), "env");
+        assert_eq!(result[0].mirror.name, "Env");
+        assert_eq!(result[0].mirror.url, "https://env-override.example.com");
+    }
+
+    #[test]
+    fn test_merge_candidates_empty_env_override_is_ignored() {
+        // An env var that is set but empty should behave as if unset.
+        let builtins = vec![Mirror::new("Official", "https://official.example.com")];
+        let result = merge_candidates(&builtins, Origin::Builtin, &[], Some(""));
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].origin, Origin::Builtin);
+    }
+
+    #[test]
+    fn test_merge_candidates_local_override_is_labeled_distinctly_from_builtin() {
+        // Entries coming from a local ~/.config/cmirror/mirrors.json override
+        // must not be indistinguishable from the compiled-in defaults.
+        let local_override = vec![Mirror::new("Custom", "https://local-override.example.com")];
+        let result = merge_candidates(&local_override, Origin::LocalOverride, &[], None);
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].origin, Origin::LocalOverride);
+        assert_eq!(result[0].origin.label(), "local-override");
+    }
+
+    #[test]
+    fn test_merge_candidates_user_entry_overrides_local_override_by_name() {
+        // mirrors.toml (Origin::User) still wins over a local mirrors.json
+        // whole-file override (Origin::LocalOverride), same as it wins over
+        // Origin::Builtin.
+        let local_override = vec![Mirror::new("Custom", "https://local-override.example.com")];
+        let user = vec![Mirror::new("Custom", "https://user-override.example.com")];
+        let result = merge_candidates(&local_override, Origin::LocalOverride, &user, None);
 
-    mirrors.get(tool_name).cloned().unwrap_or_default()
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].origin, Origin::User);
+        assert_eq!(result[0].mirror.url, "https://user-override.example.com");
+    }
 }
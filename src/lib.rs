@@ -0,0 +1,21 @@
+//! Library API for cmirror.
+//!
+//! Exposes the pieces needed to embed mirror detection/switching in other
+//! tools (or drive them from tests) without going through the CLI: the
+//! manager registry (`get_manager`), the `SourceManager` trait, the `Mirror`
+//! and `BenchmarkResult` data types, and the `benchmark_mirrors` helper.
+
+pub mod config;
+pub mod error;
+pub mod monitor;
+pub mod shell_profile;
+pub mod sources;
+pub mod traits;
+pub mod types;
+pub mod utils;
+
+pub use error::{MirrorError, Result};
+pub use sources::get_manager;
+pub use traits::SourceManager;
+pub use types::{BackupEntry, BenchmarkResult, Diagnostic, Mirror, Severity};
+pub use utils::benchmark_mirrors;
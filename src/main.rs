@@ -1,19 +1,24 @@
-mod config;
-mod error;
-mod sources;
-mod traits;
-mod types;
-mod utils;
-
 use anyhow::{bail, Result};
 use clap::{Parser, Subcommand};
-use sources::get_manager;
-use types::Mirror;
+use cmirror::sources::get_manager;
+use cmirror::types::Mirror;
+use cmirror::{config, monitor, sources, utils};
+use serde::Serialize;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "cmirror")]
 #[command(about = "A high-performance mirror manager for China", long_about = None)]
 struct Cli {
+    /// Emit machine-readable JSON instead of formatted tables (status/test)
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Don't write any files; only print what would be done (currently only
+    /// affects env-based managers like brew, which edit a shell profile)
+    #[arg(long, global = true)]
+    dry_run: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -25,7 +30,8 @@ enum Commands {
         /// The tool name (pip, docker, etc.). If omitted, shows all.
         name: Option<String>,
     },
-    /// Benchmark mirrors (e.g., cmirror test pip)
+    /// Benchmark mirrors (e.g., cmirror test pip, also available as 'cmirror bench pip')
+    #[command(alias = "bench")]
     Test {
         /// The tool name
         name: String,
@@ -39,14 +45,76 @@ enum Commands {
         #[arg(required_unless_present = "fastest")]
         source: Option<String>,
 
-        /// Auto-select the fastest mirror
-        #[arg(long, short)]
+        /// Auto-select the fastest mirror (alias: --auto)
+        #[arg(long, short, alias = "auto")]
         fastest: bool,
     },
-    /// Restore the configuration to the previous backup or default
+    /// Restore the configuration to the previous backup (or a specific one with --id)
     Restore {
         /// The tool name
         name: String,
+        /// Roll back to a specific backup id instead of the latest (see 'cmirror backups')
+        #[arg(long)]
+        id: Option<String>,
+    },
+    /// List the backup history for a tool's configuration
+    Backups {
+        /// The tool name
+        name: String,
+    },
+    /// Reset to the tool's true official source, removing any mirror override entirely
+    Reset {
+        /// The tool name
+        name: String,
+    },
+    /// Register a custom user-defined mirror (e.g., cmirror add pip MyMirror https://...)
+    Add {
+        /// The tool name
+        tool: String,
+        /// Alias for the new mirror
+        name: String,
+        /// The mirror URL
+        url: String,
+    },
+    /// Remove a previously registered custom mirror
+    Remove {
+        /// The tool name
+        tool: String,
+        /// Alias of the mirror to remove
+        name: String,
+    },
+    /// Rename or update the URL of a custom mirror
+    Rename {
+        /// The tool name
+        tool: String,
+        /// Current alias of the mirror
+        name: String,
+        /// New alias
+        #[arg(long)]
+        new_name: Option<String>,
+        /// New URL
+        #[arg(long)]
+        url: Option<String>,
+    },
+    /// Lint the current configuration for known problems (insecure URLs, duplicate
+    /// entries, suite mismatches, ...) without modifying anything
+    Check {
+        /// The tool name (pip, docker, etc.). If omitted, checks all.
+        name: Option<String>,
+    },
+    /// Run a background daemon that re-benchmarks the active source and auto-switches on degradation
+    Monitor {
+        /// The tool name
+        name: String,
+        /// Seconds between benchmark rounds
+        #[arg(long, default_value_t = 300)]
+        interval: u64,
+        /// Latency in milliseconds above which a sample counts as degraded
+        #[arg(long, default_value_t = 500)]
+        threshold: u64,
+        /// Consecutive degraded samples required before switching mirrors
+        #[arg(long, default_value_t = 3)]
+        failures: u32,
     },
 }
 
@@ -55,14 +123,31 @@ async fn main() -> Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Status { name } => handle_status(name).await?,
-        Commands::Test { name } => handle_test(&name).await?,
+        Commands::Status { name } => handle_status(name, cli.json).await?,
+        Commands::Test { name } => handle_test(&name, cli.json).await?,
         Commands::Use {
             name,
             source,
             fastest,
-        } => handle_use(&name, source, fastest).await?,
-        Commands::Restore { name } => handle_restore(&name).await?,
+        } => handle_use(&name, source, fastest, cli.dry_run).await?,
+        Commands::Restore { name, id } => handle_restore(&name, id, cli.dry_run).await?,
+        Commands::Backups { name } => handle_backups(&name, cli.json).await?,
+        Commands::Reset { name } => handle_reset(&name, cli.dry_run).await?,
+        Commands::Add { tool, name, url } => handle_add(&tool, &name, &url)?,
+        Commands::Remove { tool, name } => handle_remove(&tool, &name)?,
+        Commands::Rename {
+            tool,
+            name,
+            new_name,
+            url,
+        } => handle_rename(&tool, &name, new_name, url)?,
+        Commands::Check { name } => handle_check(name, cli.json).await?,
+        Commands::Monitor {
+            name,
+            interval,
+            threshold,
+            failures,
+        } => handle_monitor(&name, interval, threshold, failures, cli.dry_run).await?,
     }
 
     Ok(())
@@ -70,7 +155,16 @@ async fn main() -> Result<()> {
 
 // --- Handlers ---
 
-async fn handle_status(name: Option<String>) -> Result<()> {
+#[derive(Serialize)]
+struct StatusEntry {
+    tool: String,
+    current_url: Option<String>,
+    matched_candidate: Option<String>,
+    /// 当前源来自哪一层配置 ("env" / "user-config" / "local-override" / "builtin")，匹配不到候选时为 None
+    origin: Option<&'static str>,
+}
+
+async fn handle_status(name: Option<String>, json: bool) -> Result<()> {
     let tools = match name {
         Some(n) => vec![n],
         None => sources::SUPPORTED_TOOLS
@@ -79,9 +173,13 @@ async fn handle_status(name: Option<String>) -> Result<()> {
             .collect(),
     };
 
-    println!("{}", "-".repeat(70));
-    println!("{:<10} {:<40} Status", "Tool", "Current Source URL");
-    println!("{}", "-".repeat(70));
+    let mut entries = Vec::new();
+
+    if !json {
+        println!("{}", "-".repeat(70));
+        println!("{:<10} {:<40} Status", "Tool", "Current Source URL");
+        println!("{}", "-".repeat(70));
+    }
 
     for tool_name in tools {
         let manager = match get_manager(&tool_name) {
@@ -91,21 +189,34 @@ async fn handle_status(name: Option<String>) -> Result<()> {
 
         // Handle potential errors gracefully instead of crashing the whole status command
         let current_url_res = manager.current_url().await;
-        
+
         let current_url = current_url_res.unwrap_or_default();
 
-        let candidates = manager.list_candidates();
+        let candidates = config::Config::get(manager.name())?;
+
+        let matched = current_url.as_ref().and_then(|url| {
+            candidates
+                .iter()
+                .find(|c| c.mirror.url.trim_end_matches('/') == url.trim_end_matches('/'))
+        });
+        let matched_candidate = matched.map(|c| c.mirror.name.clone());
+        let origin = matched.map(|c| c.origin.label());
+
+        if json {
+            entries.push(StatusEntry {
+                tool: manager.name().to_string(),
+                current_url,
+                matched_candidate,
+                origin,
+            });
+            continue;
+        }
 
         let (url_display, status_display) = match current_url {
             Some(url) => {
-                // Check if it matches any known candidate
-                let known_name = candidates
-                    .iter()
-                    .find(|m| m.url.trim_end_matches('/') == url.trim_end_matches('/'))
-                    .map(|m| m.name.clone())
-                    .unwrap_or_else(|| "Custom".to_string());
-
-                (url, format!("[{}]", known_name))
+                let known_name = matched_candidate.unwrap_or_else(|| "Custom".to_string());
+                let suffix = origin.map(|o| format!(" (from {})", o)).unwrap_or_default();
+                (url, format!("[{}]{}", known_name, suffix))
             }
             None => ("Default".to_string(), "[Official/Default]".to_string()),
         };
@@ -123,13 +234,92 @@ async fn handle_status(name: Option<String>) -> Result<()> {
             status_display
         );
     }
-    println!("{}", "-".repeat(70));
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+    } else {
+        println!("{}", "-".repeat(70));
+    }
 
     Ok(())
 }
-async fn handle_test(name: &str) -> Result<()> {
+
+#[derive(Serialize)]
+struct CheckEntry {
+    tool: String,
+    diagnostics: Vec<cmirror::Diagnostic>,
+}
+
+async fn handle_check(name: Option<String>, json: bool) -> Result<()> {
+    let tools = match name {
+        Some(n) => vec![n],
+        None => sources::SUPPORTED_TOOLS
+            .iter()
+            .map(|&s| s.to_string())
+            .collect(),
+    };
+
+    let mut entries = Vec::new();
+    let mut has_error = false;
+
+    for tool_name in tools {
+        let manager = match get_manager(&tool_name) {
+            Ok(m) => m,
+            Err(_) => continue,
+        };
+
+        let diagnostics = manager.check().await?;
+        if diagnostics
+            .iter()
+            .any(|d| d.severity == cmirror::Severity::Error)
+        {
+            has_error = true;
+        }
+
+        if json {
+            entries.push(CheckEntry {
+                tool: manager.name().to_string(),
+                diagnostics,
+            });
+            continue;
+        }
+
+        if diagnostics.is_empty() {
+            println!("[{}] OK", manager.name());
+            continue;
+        }
+
+        for d in &diagnostics {
+            let label = match d.severity {
+                cmirror::Severity::Warning => "WARN",
+                cmirror::Severity::Error => "ERROR",
+            };
+            println!("[{}] {}: {}", manager.name(), label, d.message);
+        }
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+    }
+
+    if has_error {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct TestReport<'a> {
+    tool: &'a str,
+    current_url: Option<String>,
+    results: &'a [cmirror::BenchmarkResult],
+    fastest: Option<&'a str>,
+}
+
+async fn handle_test(name: &str, json: bool) -> Result<()> {
     let manager = get_manager(name)?;
-    let mut candidates = manager.list_candidates();
+    let mut candidates = manager.list_candidates()?;
 
     // 1. Determine the "Current" URL
     //    - If config exists, use it.
@@ -156,14 +346,33 @@ async fn handle_test(name: &str) -> Result<()> {
         }
     }
 
-    let results = utils::benchmark_mirrors(candidates).await;
-    
+    let results = utils::benchmark_mirrors(candidates, manager.probe_path()).await;
+
+    if json {
+        let fastest = results
+            .first()
+            .filter(|r| r.latency_ms < u64::MAX)
+            .map(|r| r.mirror.name.as_str());
+
+        let report = TestReport {
+            tool: name,
+            current_url: current_url_opt,
+            results: &results,
+            fastest,
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
     println!(); // Newline after progress bar
     println!(); // Additional newline for visual separation
 
     // Print Table
-    println!("{:<4} {:<10} {:<12} URL", "RANK", "LATENCY", "NAME");
-    println!("{}", "-".repeat(60));
+    println!(
+        "{:<4} {:<10} {:<12} {:<12} URL",
+        "RANK", "LATENCY", "SPEED", "NAME"
+    );
+    println!("{}", "-".repeat(70));
 
     for (i, res) in results.iter().enumerate() {
         let latency_str = if res.latency_ms == u64::MAX {
@@ -172,10 +381,17 @@ async fn handle_test(name: &str) -> Result<()> {
             format!("{}ms", res.latency_ms)
         };
 
+        let speed_str = if res.latency_ms == u64::MAX {
+            "-".to_string()
+        } else {
+            format!("{}KB/s", res.throughput_kbps)
+        };
+
         println!(
-            "{:<4} {:<10} {:<12} {}",
+            "{:<4} {:<10} {:<12} {:<12} {}",
             i + 1,
             latency_str,
+            speed_str,
             res.mirror.name,
             res.mirror.url
         );
@@ -225,8 +441,13 @@ async fn handle_test(name: &str) -> Result<()> {
     Ok(())
 }
 
-async fn handle_use(name: &str, source_name: Option<String>, fastest: bool) -> Result<()> {
-    let manager = get_manager(name)?;
+async fn handle_use(
+    name: &str,
+    source_name: Option<String>,
+    fastest: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let manager = sources::get_manager_with_options(name, dry_run)?;
 
     // 检查权限
     if manager.requires_sudo() {
@@ -240,29 +461,19 @@ async fn handle_use(name: &str, source_name: Option<String>, fastest: bool) -> R
     // 注意：整个 if-else 表达式最后需要一个分号
     let target_mirror = if fastest {
         println!("Finding fastest mirror...");
-        let results = utils::benchmark_mirrors(manager.list_candidates()).await;
 
-        // 过滤掉超时的 (u64::MAX)
-        let valid_results: Vec<_> = results
-            .into_iter()
-            .filter(|r| r.latency_ms < u64::MAX)
-            .collect();
-
-        if valid_results.is_empty() {
-            bail!("All mirrors timed out. Please check your network connection.");
+        match manager.fastest_candidate().await? {
+            Some(mirror) => {
+                println!("Fastest mirror is {}", mirror.name);
+                mirror // 返回给 target_mirror
+            }
+            None => bail!("No candidate mirrors available for '{}'.", name),
         }
-
-        let best = &valid_results[0];
-        println!(
-            "Fastest mirror is {} ({}ms)",
-            best.mirror.name, best.latency_ms
-        );
-        best.mirror.clone() // 返回给 target_mirror
     } else {
         // 按名称查找
         // unwrap 是安全的，因为 clap 配置中 required_unless_present = "fastest" 保证了 source_name 存在
         let target_name = source_name.unwrap();
-        let candidates = manager.list_candidates();
+        let candidates = manager.list_candidates()?;
 
         match candidates
             .into_iter()
@@ -283,8 +494,8 @@ async fn handle_use(name: &str, source_name: Option<String>, fastest: bool) -> R
     Ok(())
 }
 
-async fn handle_restore(name: &str) -> Result<()> {
-    let manager = get_manager(name)?;
+async fn handle_restore(name: &str, id: Option<String>, dry_run: bool) -> Result<()> {
+    let manager = sources::get_manager_with_options(name, dry_run)?;
 
     if manager.requires_sudo() {
         eprintln!(
@@ -293,9 +504,209 @@ async fn handle_restore(name: &str) -> Result<()> {
         );
     }
 
-    println!("Restoring {} configuration...", name);
-    manager.restore().await?;
-    println!("Success! {} configuration restored.", name);
+    match id {
+        Some(id) => {
+            println!("Restoring {} configuration to backup #{}...", name, id);
+            manager.restore_backup(&id).await?;
+            println!("Success! {} configuration restored to backup #{}.", name, id);
+        }
+        None => {
+            println!("Restoring {} configuration...", name);
+            manager.restore().await?;
+            println!("Success! {} configuration restored.", name);
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct BackupsReport<'a> {
+    tool: &'a str,
+    backups: Vec<cmirror::BackupEntry>,
+}
+
+async fn handle_backups(name: &str, json: bool) -> Result<()> {
+    let manager = get_manager(name)?;
+    let backups = manager.list_backups().await?;
+
+    if json {
+        let report = BackupsReport {
+            tool: name,
+            backups,
+        };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    if backups.is_empty() {
+        println!("No backups found for '{}'.", name);
+        return Ok(());
+    }
+
+    println!("{:<24} {:<22} {:<14} Original path", "ID", "TIMESTAMP", "MIRROR");
+    println!("{}", "-".repeat(80));
+    for b in &backups {
+        println!(
+            "{:<24} {:<22} {:<14} {:?}",
+            b.id,
+            b.timestamp,
+            b.mirror_name.as_deref().unwrap_or("-"),
+            b.original_path
+        );
+    }
 
     Ok(())
 }
+
+async fn handle_reset(name: &str, dry_run: bool) -> Result<()> {
+    let manager = sources::get_manager_with_options(name, dry_run)?;
+
+    if manager.requires_sudo() {
+        eprintln!(
+            "Note: Resetting {} config usually requires sudo/root permissions.",
+            name
+        );
+    }
+
+    println!("Resetting {} to the official source...", name);
+    manager.reset().await?;
+    println!("Success! {} is back to its official source.", name);
+
+    Ok(())
+}
+
+async fn handle_monitor(
+    name: &str,
+    interval_secs: u64,
+    threshold_ms: u64,
+    failures: u32,
+    dry_run: bool,
+) -> Result<()> {
+    let manager = sources::get_manager_with_options(name, dry_run)?;
+
+    println!(
+        "Starting monitor for '{}' (interval={}s, threshold={}ms, failures={})...",
+        name, interval_secs, threshold_ms, failures
+    );
+    println!("Press Ctrl-C to stop.");
+
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    let config = monitor::MonitorConfig {
+        interval: Duration::from_secs(interval_secs),
+        threshold_ms,
+        failure_limit: failures,
+    };
+
+    let monitor_name = name.to_string();
+    let handle = tokio::spawn(async move { monitor::run(manager, config, shutdown_rx).await });
+
+    tokio::signal::ctrl_c().await.ok();
+    println!("\nReceived Ctrl-C, shutting down monitor for '{}'...", monitor_name);
+    let _ = shutdown_tx.send(true);
+
+    match handle.await {
+        Ok(res) => res?,
+        Err(e) => bail!("Monitor task panicked: {}", e),
+    }
+
+    Ok(())
+}
+
+fn handle_add(tool: &str, name: &str, url: &str) -> Result<()> {
+    // 确保工具存在，避免给不支持的工具名写入垃圾条目
+    get_manager(tool)?;
+
+    config::add_user_mirror(tool, Mirror::new(name, url))?;
+    println!("Added mirror '{}' ({}) for '{}'.", name, url, tool);
+    Ok(())
+}
+
+fn handle_remove(tool: &str, name: &str) -> Result<()> {
+    get_manager(tool)?;
+
+    config::remove_user_mirror(tool, name)?;
+    println!("Removed mirror '{}' for '{}'.", name, tool);
+    Ok(())
+}
+
+fn handle_rename(
+    tool: &str,
+    name: &str,
+    new_name: Option<String>,
+    url: Option<String>,
+) -> Result<()> {
+    get_manager(tool)?;
+
+    if new_name.is_none() && url.is_none() {
+        bail!("Provide at least one of --new-name or --url to update.");
+    }
+
+    config::rename_user_mirror(tool, name, new_name.as_deref(), url.as_deref())?;
+    println!("Updated mirror '{}' for '{}'.", name, tool);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_use_fastest_flag_parses_without_explicit_source() {
+        let cli = Cli::try_parse_from(["cmirror", "use", "pip", "--fastest"]).unwrap();
+        match cli.command {
+            Commands::Use {
+                name,
+                source,
+                fastest,
+            } => {
+                assert_eq!(name, "pip");
+                assert_eq!(source, None);
+                assert!(fastest);
+            }
+            _ => panic!("expected Commands::Use"),
+        }
+    }
+
+    #[test]
+    fn test_use_auto_alias_is_equivalent_to_fastest() {
+        let cli = Cli::try_parse_from(["cmirror", "use", "pip", "--auto"]).unwrap();
+        match cli.command {
+            Commands::Use { fastest, .. } => assert!(fastest),
+            _ => panic!("expected Commands::Use"),
+        }
+    }
+
+    #[test]
+    fn test_use_without_source_or_fastest_is_rejected() {
+        // `source` is `required_unless_present = "fastest"`, so omitting both
+        // must fail to parse rather than silently defaulting.
+        assert!(Cli::try_parse_from(["cmirror", "use", "pip"]).is_err());
+    }
+
+    #[test]
+    fn test_use_with_explicit_source_does_not_set_fastest() {
+        let cli = Cli::try_parse_from(["cmirror", "use", "pip", "Aliyun"]).unwrap();
+        match cli.command {
+            Commands::Use {
+                name,
+                source,
+                fastest,
+            } => {
+                assert_eq!(name, "pip");
+                assert_eq!(source, Some("Aliyun".to_string()));
+                assert!(!fastest);
+            }
+            _ => panic!("expected Commands::Use"),
+        }
+    }
+
+    #[test]
+    fn test_bench_alias_parses_as_test_command() {
+        let cli = Cli::try_parse_from(["cmirror", "bench", "pip"]).unwrap();
+        match cli.command {
+            Commands::Test { name } => assert_eq!(name, "pip"),
+            _ => panic!("expected Commands::Test"),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::error::Result;
+use crate::traits::SourceManager;
+use crate::types::Mirror;
+use crate::utils;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time;
+
+/// 监控循环的参数
+pub struct MonitorConfig {
+    pub interval: Duration,
+    pub threshold_ms: u64,
+    pub failure_limit: u32,
+}
+
+/// 后台监控循环：定期对当前源和候选源测速，当当前源连续 `failure_limit`
+/// 次超过 `threshold_ms` (或直接超时) 时，自动切换到最快的健康候选源。
+///
+/// `shutdown` 采用 watch channel 承载停机信号 (同 Garage 的
+/// `BackgroundRunner` spawn-worker 模式)，收到 `true` 时干净退出循环。
+pub async fn run(
+    manager: Box<dyn SourceManager>,
+    config: MonitorConfig,
+    mut shutdown: watch::Receiver<bool>,
+) -> Result<()> {
+    let mut ticker = time::interval(config.interval);
+    let mut consecutive_failures: u32 = 0;
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                check_once(manager.as_ref(), &config, &mut consecutive_failures).await?;
+            }
+            _ = shutdown.changed() => {
+                if *shutdown.borrow() {
+                    println!("Monitor for '{}' stopped.", manager.name());
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 单次检查: 测速当前源 + 候选源，必要时触发自动切换
+async fn check_once(
+    manager: &dyn SourceManager,
+    config: &MonitorConfig,
+    consecutive_failures: &mut u32,
+) -> Result<()> {
+    let mut candidates = manager.list_candidates()?;
+    let current_url = manager.current_url().await.ok().flatten();
+
+    // 当前源不在候选列表里 (用户自定义的源) 时也要一并测速
+    if let Some(url) = &current_url {
+        let is_known = candidates
+            .iter()
+            .any(|m| m.url.trim_end_matches('/') == url.trim_end_matches('/'));
+        if !is_known {
+            candidates.push(Mirror::new("Current", url));
+        }
+    }
+
+    let results = utils::benchmark_mirrors(candidates, manager.probe_path()).await;
+
+    let current_result = current_url.as_ref().and_then(|url| {
+        results
+            .iter()
+            .find(|r| r.mirror.url.trim_end_matches('/') == url.trim_end_matches('/'))
+    });
+
+    // 如果无法识别当前源（比如还没配置过），不参与失败计数，避免误触发切换
+    let degraded = match current_result {
+        Some(r) => r.latency_ms == u64::MAX || r.latency_ms > config.threshold_ms,
+        None => false,
+    };
+
+    if degraded {
+        *consecutive_failures += 1;
+        println!(
+            "[monitor:{}] current source degraded ({}/{} over threshold)",
+            manager.name(),
+            consecutive_failures,
+            config.failure_limit
+        );
+    } else {
+        *consecutive_failures = 0;
+    }
+
+    if *consecutive_failures >= config.failure_limit {
+        if let Some(best) = results.iter().find(|r| r.latency_ms < u64::MAX) {
+            let old_latency = current_result.map(|r| r.latency_ms);
+
+            manager.set_source(&best.mirror).await?;
+
+            match old_latency {
+                Some(old) if old < u64::MAX && best.latency_ms > 0 => {
+                    let speedup = old as f64 / best.latency_ms as f64;
+                    println!(
+                        "[monitor:{}] switched to '{}' ({:.1}x faster, {}ms -> {}ms)",
+                        manager.name(),
+                        best.mirror.name,
+                        speedup,
+                        old,
+                        best.latency_ms
+                    );
+                }
+                _ => {
+                    println!(
+                        "[monitor:{}] switched to '{}' ({}ms)",
+                        manager.name(),
+                        best.mirror.name,
+                        best.latency_ms
+                    );
+                }
+            }
+
+            *consecutive_failures = 0;
+        } else {
+            println!(
+                "[monitor:{}] current source degraded but all candidates timed out, keeping current source",
+                manager.name()
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,173 @@
+//! 向用户 shell 启动文件写入/移除受管代码块的小工具。
+//!
+//! 像 `brew` 这类纯环境变量驱动的工具没有自己的配置文件，`cmirror` 只能
+//! 通过 `export FOO=bar` 一类语句让改动跨 shell 会话生效。这里统一处理
+//! "识别用户在用哪个 shell、用什么语法写变量、如何安全地复写/撤销" 这几件事，
+//! 避免每个 env-based manager 各写一套。
+
+use crate::error::Result;
+use directories::BaseDirs;
+use std::path::PathBuf;
+use tokio::fs;
+
+const MARKER_BEGIN: &str = "# >>> cmirror managed >>>";
+const MARKER_END: &str = "# <<< cmirror managed <<<";
+
+/// 已识别的 shell 种类，决定写入变量时使用的语法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellKind {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// 一个已定位的 shell 启动文件
+pub struct ShellProfile {
+    pub kind: ShellKind,
+    pub path: PathBuf,
+}
+
+impl ShellProfile {
+    /// 优先读取 `$SHELL` 判断当前 shell 及其对应的启动文件；
+    /// 如果 `$SHELL` 缺失或无法识别，退化为依次检查常见 profile 文件是否存在。
+    pub fn detect() -> Option<Self> {
+        let home = BaseDirs::new()?.home_dir().to_path_buf();
+
+        if let Ok(shell) = std::env::var("SHELL") {
+            if shell.contains("fish") {
+                return Some(Self {
+                    kind: ShellKind::Fish,
+                    path: home.join(".config").join("fish").join("config.fish"),
+                });
+            }
+            if shell.contains("zsh") {
+                return Some(Self {
+                    kind: ShellKind::Zsh,
+                    path: home.join(".zshrc"),
+                });
+            }
+            if shell.contains("bash") {
+                let bash_profile = home.join(".bash_profile");
+                let path = if bash_profile.exists() {
+                    bash_profile
+                } else {
+                    home.join(".bashrc")
+                };
+                return Some(Self {
+                    kind: ShellKind::Bash,
+                    path,
+                });
+            }
+        }
+
+        let candidates = [
+            (home.join(".zshrc"), ShellKind::Zsh),
+            (home.join(".bashrc"), ShellKind::Bash),
+            (home.join(".bash_profile"), ShellKind::Bash),
+            (home.join(".config").join("fish").join("config.fish"), ShellKind::Fish),
+        ];
+        candidates
+            .into_iter()
+            .find(|(path, _)| path.exists())
+            .map(|(path, kind)| Self { kind, path })
+    }
+
+    fn format_export(&self, var: &str, value: &str) -> String {
+        match self.kind {
+            ShellKind::Fish => format!("set -gx {} {}", var, value),
+            ShellKind::Bash | ShellKind::Zsh => format!("export {}=\"{}\"", var, value),
+        }
+    }
+
+    fn backup_path(&self) -> PathBuf {
+        let mut name = self.path.as_os_str().to_os_string();
+        name.push(".cmirror.bak");
+        PathBuf::from(name)
+    }
+
+    /// 将一组环境变量写入受管代码块：如果文件中已存在该代码块则原地替换，
+    /// 否则追加到文件末尾。写入前总会把原文件完整备份到 `<file>.cmirror.bak`。
+    pub async fn write_vars(&self, vars: &[(&str, &str)]) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        let content = if fs::try_exists(&self.path).await.unwrap_or(false) {
+            fs::read_to_string(&self.path).await?
+        } else {
+            String::new()
+        };
+
+        if !content.is_empty() {
+            fs::write(self.backup_path(), &content).await?;
+        }
+
+        let mut block = String::new();
+        block.push_str(MARKER_BEGIN);
+        block.push('\n');
+        for (var, value) in vars {
+            block.push_str(&self.format_export(var, value));
+            block.push('\n');
+        }
+        block.push_str(MARKER_END);
+
+        fs::write(&self.path, replace_managed_block(&content, &block)).await?;
+        Ok(())
+    }
+
+    /// 从 profile 文件中整体删除受管代码块 (用于 restore)；文件不存在或没有
+    /// 受管代码块时视为已经是期望状态，直接返回成功。
+    pub async fn remove_block(&self) -> Result<()> {
+        if !fs::try_exists(&self.path).await.unwrap_or(false) {
+            return Ok(());
+        }
+
+        let content = fs::read_to_string(&self.path).await?;
+        if !content.contains(MARKER_BEGIN) {
+            return Ok(());
+        }
+
+        fs::write(self.backup_path(), &content).await?;
+        fs::write(&self.path, strip_managed_block(&content)).await?;
+        Ok(())
+    }
+}
+
+/// 在 `content` 中原地替换受管代码块 (不存在则追加到末尾)
+fn replace_managed_block(content: &str, block: &str) -> String {
+    if let Some(start) = content.find(MARKER_BEGIN) {
+        if let Some(end_rel) = content[start..].find(MARKER_END) {
+            let end = start + end_rel + MARKER_END.len();
+            return format!("{}{}{}", &content[..start], block, &content[end..]);
+        }
+    }
+
+    if content.is_empty() {
+        format!("{}\n", block)
+    } else if content.ends_with('\n') {
+        format!("{}{}\n", content, block)
+    } else {
+        format!("{}\n{}\n", content, block)
+    }
+}
+
+/// 删除受管代码块，同时吃掉前后多余的空行，避免留下一片空白
+fn strip_managed_block(content: &str) -> String {
+    let Some(start) = content.find(MARKER_BEGIN) else {
+        return content.to_string();
+    };
+    let Some(end_rel) = content[start..].find(MARKER_END) else {
+        return content.to_string();
+    };
+    let end = start + end_rel + MARKER_END.len();
+
+    let before = content[..start].trim_end_matches('\n');
+    let after = content[end..].trim_start_matches('\n');
+
+    match (before.is_empty(), after.is_empty()) {
+        (true, true) => String::new(),
+        (true, false) => after.to_string(),
+        (false, true) => format!("{}\n", before),
+        (false, false) => format!("{}\n\n{}", before, after),
+    }
+}
@@ -1,5 +1,5 @@
 use crate::traits::SourceManager;
-use crate::types::Mirror;
+use crate::types::{BackupEntry, Diagnostic, Mirror};
 use crate::error::{Result, MirrorError};
 use crate::config;
 use crate::utils;
@@ -8,31 +8,219 @@ use regex::Regex;
 use std::path::PathBuf;
 use tokio::fs;
 
+// 按时间顺序排列的 Ubuntu / Debian 历史 codename，用于 codename -> distro 反查
+// 以及 EOL 判断。维护列表本身会过时，但比完全没有校验要好。
+const UBUNTU_CODENAMES: &[&str] = &[
+    "warty", "hoary", "breezy", "dapper", "edgy", "feisty", "gutsy", "hardy",
+    "intrepid", "jaunty", "karmic", "lucid", "maverick", "natty", "oneiric",
+    "precise", "quantal", "raring", "saucy", "trusty", "utopic", "vivid", "wily",
+    "xenial", "yakkety", "zesty", "artful", "bionic", "cosmic", "disco", "eoan",
+    "focal", "groovy", "hirsute", "impish", "jammy", "kinetic", "lunar",
+    "mantic", "noble", "oracular",
+];
+
+const DEBIAN_CODENAMES: &[&str] = &[
+    "buzz", "rex", "bo", "hamm", "slink", "potato", "woody", "sarge", "etch",
+    "lenny", "squeeze", "wheezy", "jessie", "stretch", "buster", "bullseye",
+    "bookworm", "trixie",
+];
+
+// 当前仍在支持周期内的 codename；不在这里的视为 EOL
+const UBUNTU_SUPPORTED_CODENAMES: &[&str] = &["jammy", "noble", "oracular"];
+const DEBIAN_SUPPORTED_CODENAMES: &[&str] = &["bullseye", "bookworm", "trixie"];
+
+fn codename_to_distro(codename: &str) -> Option<&'static str> {
+    if UBUNTU_CODENAMES.contains(&codename) {
+        Some("ubuntu")
+    } else if DEBIAN_CODENAMES.contains(&codename) {
+        Some("debian")
+    } else {
+        None
+    }
+}
+
+fn is_eol_codename(codename: &str) -> bool {
+    if UBUNTU_CODENAMES.contains(&codename) {
+        return !UBUNTU_SUPPORTED_CODENAMES.contains(&codename);
+    }
+    if DEBIAN_CODENAMES.contains(&codename) {
+        return !DEBIAN_SUPPORTED_CODENAMES.contains(&codename);
+    }
+    // 未知 codename：无法判断，不要因此阻塞用户
+    false
+}
+
+/// 校验一组 suite (如 `["jammy", "jammy-updates"]`) 是否与探测到的 release
+/// codename 一致 (允许 `-updates`/`-security`/`-backports` 变体)。如果引用了
+/// 别的 codename，或者该 codename 已经 EOL，返回 Err 作为警告；调用方应在
+/// 真正覆盖配置文件之前传播这个错误，而不是静默写入。
+fn validate_suites(suites: &[String], codename: &str) -> Result<()> {
+    if is_eol_codename(codename) {
+        return Err(MirrorError::Custom(format!(
+            "Release codename '{}' is end-of-life; its mirrors may no longer be maintained.",
+            codename
+        )));
+    }
+
+    for suite in suites {
+        let base = suite.split('-').next().unwrap_or(suite);
+        if base != codename {
+            return Err(MirrorError::Custom(format!(
+                "Suite '{}' does not match the detected release codename '{}'.",
+                suite, codename
+            )));
+        }
+    }
+
+    Ok(())
+}
+
 pub struct AptManager {
     distro: String,
+    codename: Option<String>,
     custom_path: Option<PathBuf>,
+    custom_sources_dir: Option<PathBuf>,
 }
 
 impl AptManager {
     pub fn new() -> Self {
-        // Simple heuristic detection (synchronous is fine here for construction, 
+        // Simple heuristic detection (synchronous is fine here for construction,
         // or we can detect lazily. For now, let's try to detect once).
         // Since we are inside a specific tool, we can try to read /etc/os-release
-        let distro = Self::detect_distro().unwrap_or_else(|| "ubuntu".to_string());
-        Self { 
+        let codename = Self::detect_codename();
+        let distro = Self::detect_distro()
+            .or_else(|| codename.as_deref().and_then(codename_to_distro).map(String::from))
+            .unwrap_or_else(|| "ubuntu".to_string());
+        Self {
             distro,
-            custom_path: None 
+            codename,
+            custom_path: None,
+            custom_sources_dir: None,
         }
     }
 
     #[cfg(test)]
     pub fn with_distro_and_path(distro: String, path: PathBuf) -> Self {
+        // Point deb822 lookup at a sibling directory that is guaranteed not to
+        // exist, so tests exercising the legacy sources.list path never fall
+        // back to the host's real /etc/apt/sources.list.d (see find_deb822_files).
+        let isolated_sources_dir = path.with_file_name("sources.list.d-unused");
         Self {
             distro,
+            codename: None,
             custom_path: Some(path),
+            custom_sources_dir: Some(isolated_sources_dir),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn with_distro_and_sources_dir(distro: String, sources_dir: PathBuf) -> Self {
+        Self {
+            distro,
+            codename: None,
+            custom_path: None,
+            custom_sources_dir: Some(sources_dir),
         }
     }
 
+    /// 构造链式方法：注入一个确定的 release codename 用于测试
+    #[cfg(test)]
+    pub fn with_codename(mut self, codename: impl Into<String>) -> Self {
+        self.codename = Some(codename.into());
+        self
+    }
+
+    /// 目录: /etc/apt/sources.list.d，现代 deb822 `*.sources` 文件所在位置
+    fn sources_dir(&self) -> PathBuf {
+        self.custom_sources_dir
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("/etc/apt/sources.list.d"))
+    }
+
+    /// 列出 sources.list.d 目录下所有 deb822 `*.sources` 文件 (按文件名排序)
+    async fn find_deb822_files(&self) -> Vec<PathBuf> {
+        let dir = self.sources_dir();
+        let mut files = Vec::new();
+
+        let Ok(mut entries) = fs::read_dir(&dir).await else {
+            return files;
+        };
+
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            if path.extension().map(|ext| ext == "sources").unwrap_or(false) {
+                files.push(path);
+            }
+        }
+
+        files.sort();
+        files
+    }
+
+    /// 从 deb822 文件内容中提取第一个 `URIs:` 字段的第一个 URL
+    fn first_deb822_uri(content: &str) -> Option<String> {
+        let re = Regex::new(r"(?m)^URIs:\s*(.+)$").ok()?;
+        let caps = re.captures(content)?;
+        caps[1].split_whitespace().next().map(|s| s.to_string())
+    }
+
+    /// 将 deb822 内容里所有 `URIs:` 字段中出现的 `old_url` 替换为 `new_url`，
+    /// 其余字段、注释、空行和缩进原样保留，保证幂等 (未变化时重写结果不变)。
+    fn rewrite_deb822_uris(content: &str, old_url: &str, new_url: &str) -> String {
+        let old_trimmed = old_url.trim_end_matches('/');
+        let new_trimmed = new_url.trim_end_matches('/');
+
+        let mut out = String::with_capacity(content.len());
+        for line in content.split_inclusive('\n') {
+            if let Some(rest) = line.strip_prefix("URIs:") {
+                out.push_str("URIs:");
+                out.push_str(&rest.replace(old_trimmed, new_trimmed));
+            } else {
+                out.push_str(line);
+            }
+        }
+        out
+    }
+
+    /// 从 `/etc/os-release` 读取 release codename：优先 `VERSION_CODENAME`
+    /// (Debian/Ubuntu 通用)，其次 `UBUNTU_CODENAME` (仅 Ubuntu 派生版存在)
+    fn detect_codename() -> Option<String> {
+        let content = std::fs::read_to_string("/etc/os-release").ok()?;
+
+        let mut ubuntu_codename = None;
+        for line in content.lines() {
+            if let Some(value) = line.strip_prefix("VERSION_CODENAME=") {
+                return Some(value.trim_matches('"').to_string());
+            }
+            if let Some(value) = line.strip_prefix("UBUNTU_CODENAME=") {
+                ubuntu_codename = Some(value.trim_matches('"').to_string());
+            }
+        }
+
+        ubuntu_codename
+    }
+
+    /// 从 deb822 内容中提取 `Suites:` 字段里的全部 suite token
+    fn deb822_suites(content: &str) -> Vec<String> {
+        let Ok(re) = Regex::new(r"(?m)^Suites:\s*(.+)$") else {
+            return Vec::new();
+        };
+        let Some(caps) = re.captures(content) else {
+            return Vec::new();
+        };
+        caps[1].split_whitespace().map(|s| s.to_string()).collect()
+    }
+
+    /// 从传统 sources.list 内容中提取所有活跃 `deb` 行引用的 suite
+    fn legacy_suites(content: &str) -> Vec<String> {
+        let Ok(re) = Regex::new(r"(?m)^deb\s+(?:\[.*?\]\s+)?https?://\S+\s+(\S+)") else {
+            return Vec::new();
+        };
+        re.captures_iter(content)
+            .map(|caps| caps[1].to_string())
+            .collect()
+    }
+
     fn detect_distro() -> Option<String> {
         // Quick check of os-release
         if let Ok(content) = std::fs::read_to_string("/etc/os-release") {
@@ -67,7 +255,7 @@ impl SourceManager for AptManager {
         true
     }
 
-    fn list_candidates(&self) -> Vec<Mirror> {
+    fn list_candidates(&self) -> Result<Vec<Mirror>> {
         let key = format!("apt-{}", self.distro);
         config::get_candidates(&key)
     }
@@ -80,17 +268,31 @@ impl SourceManager for AptManager {
     }
 
     async fn current_url(&self) -> Result<Option<String>> {
+        // Modern deb822 `*.sources` files take priority when present.
+        let deb822_files = self.find_deb822_files().await;
+        if !deb822_files.is_empty() {
+            for file in &deb822_files {
+                if let Ok(content) = fs::read_to_string(file).await {
+                    if let Some(url) = Self::first_deb822_uri(&content) {
+                        return Ok(Some(url));
+                    }
+                }
+            }
+            return Ok(None);
+        }
+
+        // Legacy single-line sources.list
         let path = self.config_path();
         if !fs::try_exists(&path).await.unwrap_or(false) {
             return Ok(None);
         }
 
         let content = fs::read_to_string(&path).await?;
-        
+
         // Find the first active 'deb' line
         // Regex: ^deb\s+(?:\[.*?\]\s+)?(\S+)\s+
         let re = Regex::new(r"(?m)^deb\s+(?:\[.*?\]\s+)?(?P<url>https?://\S+)\s+")?;
-        
+
         if let Some(caps) = re.captures(&content) {
             Ok(Some(caps["url"].to_string()))
         } else {
@@ -99,32 +301,82 @@ impl SourceManager for AptManager {
     }
 
     async fn set_source(&self, mirror: &Mirror) -> Result<()> {
+        let target_url = if mirror.url.ends_with('/') {
+            mirror.url.clone()
+        } else {
+            format!("{}/", mirror.url)
+        };
+
+        let deb822_files = self.find_deb822_files().await;
+        if !deb822_files.is_empty() {
+            let mut found_any = false;
+
+            for file in &deb822_files {
+                let content = fs::read_to_string(file).await?;
+                let Some(current) = Self::first_deb822_uri(&content) else {
+                    continue;
+                };
+                found_any = true;
+
+                if let Some(codename) = &self.codename {
+                    let suites = Self::deb822_suites(&content);
+                    if !suites.is_empty() {
+                        validate_suites(&suites, codename)?;
+                    }
+                }
+
+                let new_content = Self::rewrite_deb822_uris(&content, &current, &target_url);
+                // No-op if the mirror is already applied: skip the backup/write
+                // so re-running `set_source` with the same mirror is a true no-op.
+                if new_content != content {
+                    // 标签对应被覆盖的旧内容 (current)，而不是即将写入的 mirror
+                    let label = utils::resolve_backup_label(&self.list_candidates()?, Some(&current));
+                    utils::backup_file(file, label.as_deref()).await?;
+                    fs::write(file, new_content).await?;
+                }
+            }
+
+            if !found_any {
+                return Err(MirrorError::Custom(
+                    "No 'URIs:' field found in any deb822 .sources file to rewrite".to_string(),
+                ));
+            }
+
+            return Ok(());
+        }
+
+        // Legacy single-line sources.list
         let path = self.config_path();
         if !fs::try_exists(&path).await.unwrap_or(false) {
              return Err(MirrorError::Custom(format!("Config file not found: {:?}", path)));
         }
 
         let content = fs::read_to_string(&path).await?;
-        utils::backup_file(&path).await?;
 
-        // Strategy: Replace the base URL of the main repo.
-        // We need to know what the CURRENT URL is to replace it.
-        // But the user might have mixed sources. 
-        // Safe bet: Replace lines that look like the distro's main repo.
-        
-        let target_url = if mirror.url.ends_with('/') {
-            mirror.url.clone()
-        } else {
-            format!("{}/", mirror.url)
-        };
+        if let Some(codename) = &self.codename {
+            let suites = Self::legacy_suites(&content);
+            if !suites.is_empty() {
+                validate_suites(&suites, codename)?;
+            }
+        }
 
         // Determine what to replace.
         // If we found a current URL, replace IT.
         let current = self.current_url().await?;
-        
+
+        // Backup label: the content being overwritten belongs to `current`,
+        // not to the incoming `mirror`
+        let label = utils::resolve_backup_label(&self.list_candidates()?, current.as_deref());
+        utils::backup_file(&path, label.as_deref()).await?;
+
+        // Strategy: Replace the base URL of the main repo.
+        // We need to know what the CURRENT URL is to replace it.
+        // But the user might have mixed sources.
+        // Safe bet: Replace lines that look like the distro's main repo.
+
         let new_content = if let Some(cur_url) = current {
             // Replace all occurrences of current_url with mirror.url
-            // Note: Use simple string replacement to avoid regex escaping issues, 
+            // Note: Use simple string replacement to avoid regex escaping issues,
             // but be careful about partial matches.
             content.replace(&cur_url, &target_url)
         } else {
@@ -135,7 +387,7 @@ impl SourceManager for AptManager {
             } else {
                 vec!["deb.debian.org/debian/", "security.debian.org/debian/"]
             };
-            
+
             let mut modified = content.clone();
             for domain in default_domains {
                 // Try to replace HTTP and HTTPS variants
@@ -150,8 +402,145 @@ impl SourceManager for AptManager {
     }
 
     async fn restore(&self) -> Result<()> {
+        let deb822_files = self.find_deb822_files().await;
+        if !deb822_files.is_empty() {
+            let mut restored_any = false;
+            for file in &deb822_files {
+                if utils::restore_latest_backup(file).await.is_ok() {
+                    restored_any = true;
+                }
+            }
+
+            if !restored_any {
+                return Err(MirrorError::Custom(
+                    "No backups found for any deb822 .sources file.".to_string(),
+                ));
+            }
+
+            return Ok(());
+        }
+
         utils::restore_latest_backup(&self.config_path()).await
     }
+
+    async fn reset(&self) -> Result<()> {
+        // Unlike pip/npm/cargo, apt can't just delete the mirror entry:
+        // sources.list always needs *some* valid URL. So "reset" means
+        // pointing back at the tool's true official domain instead.
+        let official_url = if self.distro == "ubuntu" {
+            "http://archive.ubuntu.com/ubuntu/"
+        } else {
+            "http://deb.debian.org/debian/"
+        };
+
+        self.set_source(&Mirror::new("Official", official_url)).await
+    }
+
+    async fn check(&self) -> Result<Vec<Diagnostic>> {
+        let mut diagnostics = Vec::new();
+
+        let deb822_files = self.find_deb822_files().await;
+        if !deb822_files.is_empty() {
+            for file in &deb822_files {
+                let Ok(content) = fs::read_to_string(file).await else {
+                    continue;
+                };
+
+                if let Some(url) = Self::first_deb822_uri(&content) {
+                    if url.starts_with("http://") {
+                        diagnostics.push(
+                            Diagnostic::warning(format!(
+                                "apt is using an insecure http:// mirror: {}",
+                                url
+                            ))
+                            .with_file(file.clone()),
+                        );
+                    }
+                }
+
+                let suites = Self::deb822_suites(&content);
+                if let Some(codename) = &self.codename {
+                    if !suites.is_empty() {
+                        if let Err(e) = validate_suites(&suites, codename) {
+                            diagnostics.push(Diagnostic::error(e.to_string()).with_file(file.clone()));
+                        }
+                    }
+                }
+            }
+
+            return Ok(diagnostics);
+        }
+
+        let path = self.config_path();
+        if !fs::try_exists(&path).await.unwrap_or(false) {
+            return Ok(diagnostics);
+        }
+
+        let content = fs::read_to_string(&path).await?;
+
+        if let Some(url) = self.current_url().await? {
+            if url.starts_with("http://") {
+                diagnostics.push(
+                    Diagnostic::warning(format!("apt is using an insecure http:// mirror: {}", url))
+                        .with_file(path.clone()),
+                );
+            }
+        }
+
+        let suites = Self::legacy_suites(&content);
+        if let Some(codename) = &self.codename {
+            if !suites.is_empty() {
+                if let Err(e) = validate_suites(&suites, codename) {
+                    diagnostics.push(Diagnostic::error(e.to_string()).with_file(path.clone()));
+                }
+            }
+        }
+
+        Ok(diagnostics)
+    }
+
+    async fn list_backups(&self) -> Result<Vec<BackupEntry>> {
+        let deb822_files = self.find_deb822_files().await;
+        if !deb822_files.is_empty() {
+            // 每个 deb822 文件有自己独立的备份目录，用文件名给 id 加前缀以保证
+            // 跨文件唯一，格式为 "<文件名>:<该文件内的序号>"。
+            let mut all = Vec::new();
+            for file in &deb822_files {
+                let file_tag = file.file_name().unwrap_or_default().to_string_lossy().to_string();
+                let mut entries = utils::list_backups(file).await?;
+                for entry in &mut entries {
+                    entry.id = format!("{}:{}", file_tag, entry.id);
+                }
+                all.extend(entries);
+            }
+            return Ok(all);
+        }
+
+        utils::list_backups(&self.config_path()).await
+    }
+
+    async fn restore_backup(&self, id: &str) -> Result<()> {
+        let deb822_files = self.find_deb822_files().await;
+        if !deb822_files.is_empty() {
+            let (file_tag, plain_id) = id.split_once(':').ok_or_else(|| {
+                MirrorError::Custom(format!(
+                    "Invalid backup id '{}': expected '<file>:<index>' for deb822 sources",
+                    id
+                ))
+            })?;
+
+            let file = deb822_files
+                .iter()
+                .find(|f| f.file_name().unwrap_or_default().to_string_lossy() == file_tag)
+                .ok_or_else(|| {
+                    MirrorError::Custom(format!("No deb822 .sources file named '{}' found", file_tag))
+                })?;
+
+            return utils::restore_backup(file, plain_id).await;
+        }
+
+        utils::restore_backup(&self.config_path(), id).await
+    }
 }
 
 #[cfg(test)]
@@ -182,10 +571,7 @@ deb http://security.ubuntu.com/ubuntu/ jammy-security main restricted
         assert_eq!(manager.current_url().await?, Some("http://archive.ubuntu.com/ubuntu/".to_string()));
 
         // 2. Set source
-        let mirror = Mirror {
-            name: "TestApt".to_string(),
-            url: "http://mirrors.test.com/ubuntu/".to_string(),
-        };
+        let mirror = Mirror::new("TestApt", "http://mirrors.test.com/ubuntu/");
         manager.set_source(&mirror).await?;
 
         // 3. Check file content
@@ -214,4 +600,128 @@ deb http://security.ubuntu.com/ubuntu/ jammy-security main restricted
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_apt_deb822_flow() -> Result<()> {
+        let dir = tempdir()?;
+        let sources_file = dir.path().join("ubuntu.sources");
+
+        let initial_content = "Types: deb\n\
+URIs: http://archive.ubuntu.com/ubuntu/\n\
+Suites: jammy jammy-updates\n\
+Components: main restricted\n\
+# a trailing comment that must survive untouched\n";
+        fs::write(&sources_file, initial_content).await?;
+
+        let manager =
+            AptManager::with_distro_and_sources_dir("ubuntu".to_string(), dir.path().to_path_buf());
+
+        // 1. Detect current URL from the deb822 stanza
+        assert_eq!(
+            manager.current_url().await?,
+            Some("http://archive.ubuntu.com/ubuntu/".to_string())
+        );
+
+        // 2. Set a new mirror
+        let mirror = Mirror::new("TestApt", "https://mirrors.test.com/ubuntu/");
+        manager.set_source(&mirror).await?;
+
+        let new_content = fs::read_to_string(&sources_file).await?;
+        assert!(new_content.contains("URIs: https://mirrors.test.com/ubuntu/"));
+        // Everything else must be untouched
+        assert!(new_content.contains("Types: deb"));
+        assert!(new_content.contains("Suites: jammy jammy-updates"));
+        assert!(new_content.contains("Components: main restricted"));
+        assert!(new_content.contains("# a trailing comment that must survive untouched"));
+
+        // 3. Idempotent round-trip: re-applying the same mirror changes nothing
+        let before_reapply = fs::read_to_string(&sources_file).await?;
+        manager.set_source(&mirror).await?;
+        let after_reapply = fs::read_to_string(&sources_file).await?;
+        assert_eq!(before_reapply, after_reapply);
+
+        // 4. Restore
+        manager.restore().await?;
+        let restored_content = fs::read_to_string(&sources_file).await?;
+        assert_eq!(restored_content, initial_content);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_apt_suite_mismatch_blocks_overwrite() -> Result<()> {
+        // The file is pinned to "jammy", but the manager thinks the host is
+        // running "noble": set_source must refuse before touching the file.
+        let dir = tempdir()?;
+        let sources_file = dir.path().join("ubuntu.sources");
+
+        let initial_content = "Types: deb\n\
+URIs: http://archive.ubuntu.com/ubuntu/\n\
+Suites: jammy jammy-updates\n\
+Components: main restricted\n";
+        fs::write(&sources_file, initial_content).await?;
+
+        let manager =
+            AptManager::with_distro_and_sources_dir("ubuntu".to_string(), dir.path().to_path_buf())
+                .with_codename("noble");
+
+        let mirror = Mirror::new("TestApt", "https://mirrors.test.com/ubuntu/");
+        assert!(manager.set_source(&mirror).await.is_err());
+
+        // File must be untouched
+        let content = fs::read_to_string(&sources_file).await?;
+        assert_eq!(content, initial_content);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_apt_eol_codename_blocks_overwrite() -> Result<()> {
+        // "precise" (12.04) is long EOL; even a matching suite should warn.
+        let dir = tempdir()?;
+        let config_path = dir.path().join("sources.list");
+
+        let initial_content = "deb http://archive.ubuntu.com/ubuntu/ precise main restricted\n";
+        fs::write(&config_path, initial_content).await?;
+
+        let manager = AptManager::with_distro_and_path("ubuntu".to_string(), config_path.clone())
+            .with_codename("precise");
+
+        let mirror = Mirror::new("TestApt", "http://mirrors.test.com/ubuntu/");
+        assert!(manager.set_source(&mirror).await.is_err());
+
+        let content = fs::read_to_string(&config_path).await?;
+        assert_eq!(content, initial_content);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_apt_check_flags_insecure_and_suite_mismatch() -> Result<()> {
+        let dir = tempdir()?;
+        let sources_file = dir.path().join("ubuntu.sources");
+
+        let initial_content = "Types: deb\n\
+URIs: http://archive.ubuntu.com/ubuntu/\n\
+Suites: jammy jammy-updates\n\
+Components: main restricted\n";
+        fs::write(&sources_file, initial_content).await?;
+
+        let manager =
+            AptManager::with_distro_and_sources_dir("ubuntu".to_string(), dir.path().to_path_buf())
+                .with_codename("noble");
+
+        let diagnostics = manager.check().await?;
+        assert!(diagnostics.iter().any(|d| d.message.contains("insecure http://")));
+        assert!(diagnostics.iter().any(|d| d.message.contains("does not match")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_codename_to_distro_mapping() {
+        assert_eq!(codename_to_distro("jammy"), Some("ubuntu"));
+        assert_eq!(codename_to_distro("bookworm"), Some("debian"));
+        assert_eq!(codename_to_distro("nonexistent"), None);
+    }
 }
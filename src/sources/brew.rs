@@ -1,15 +1,43 @@
 use crate::config;
 use crate::error::Result;
+use crate::shell_profile::{ShellKind, ShellProfile};
 use crate::traits::SourceManager;
 use crate::types::Mirror;
 use async_trait::async_trait;
 use std::path::PathBuf;
 
-pub struct BrewManager;
+pub struct BrewManager {
+    /// 为 true 时只打印将要执行的 shell 变量变更，不实际改写 profile 文件
+    dry_run: bool,
+    /// 测试专用：跳过 `ShellProfile::detect()` 对真实 `$SHELL`/home 目录的依赖，
+    /// 直接把受管代码块写入这个固定路径
+    test_profile_path: Option<PathBuf>,
+}
 
 impl BrewManager {
-    pub fn new() -> Self {
-        Self
+    pub fn new(dry_run: bool) -> Self {
+        Self {
+            dry_run,
+            test_profile_path: None,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn with_profile_path(dry_run: bool, path: PathBuf) -> Self {
+        Self {
+            dry_run,
+            test_profile_path: Some(path),
+        }
+    }
+
+    fn resolve_profile(&self) -> Option<ShellProfile> {
+        if let Some(path) = &self.test_profile_path {
+            return Some(ShellProfile {
+                kind: ShellKind::Bash,
+                path: path.clone(),
+            });
+        }
+        ShellProfile::detect()
     }
 }
 
@@ -23,7 +51,7 @@ impl SourceManager for BrewManager {
         false
     }
 
-    fn list_candidates(&self) -> Vec<Mirror> {
+    fn list_candidates(&self) -> Result<Vec<Mirror>> {
         config::get_candidates("brew")
     }
 
@@ -45,40 +73,159 @@ impl SourceManager for BrewManager {
     }
 
     async fn set_source(&self, mirror: &Mirror) -> Result<()> {
-        // Since we cannot reliably edit user's shell profile (.zshrc, .bashrc, .config/fish/...)
-        // without risk, and `export` only affects current session,
-        // we will display the commands the user needs to run.
-        //
-        // Ideally, `cmirror` would append to the shell profile, but detecting the shell and file is hard.
-        // For MVP, we print instructions.
-
-        println!("To apply this mirror, please run the following commands in your terminal:");
-        println!();
-        println!("    export HOMEBREW_API_DOMAIN \"{}\"", mirror.url);
-
-        // Some mirrors also suggest BOTTLE_DOMAIN, but our JSON currently only tracks one URL.
-        // If the URL matches known providers (Tuna/USTC), we can infer the bottle domain.
-        if mirror.url.contains("tuna") {
-            println!("    export HOMEBREW_BOTTLE_DOMAIN=\"https://mirrors.tuna.tsinghua.edu.cn/homebrew-bottles\"");
-        } else if mirror.url.contains("ustc") {
-            println!("    export HOMEBREW_BOTTLE_DOMAIN=\"https://mirrors.ustc.edu.cn/homebrew-bottles\"");
+        // brew 没有自己的配置文件，唯一能持久化的地方是用户的 shell 启动文件。
+        // HOMEBREW_API_DOMAIN 总是来自 url 本身；bottle/artifact 域名则来自
+        // mirror.endpoints，由镜像条目自己声明，而不是靠猜测 URL 里的关键字。
+        let mut vars = vec![("HOMEBREW_API_DOMAIN".to_string(), mirror.url.clone())];
+
+        if let Some(bottle) = mirror.endpoint("bottle") {
+            vars.push(("HOMEBREW_BOTTLE_DOMAIN".to_string(), bottle.to_string()));
+        }
+        if let Some(artifact) = mirror.endpoint("artifact") {
+            vars.push(("HOMEBREW_ARTIFACT_DOMAIN".to_string(), artifact.to_string()));
+        }
+
+        if self.dry_run {
+            print_manual_instructions(&vars, "export", "unset");
+            return Ok(());
         }
 
-        println!();
-        println!("To make it permanent, add the above lines to your ~/.zshrc or ~/.bash_profile.");
+        match self.resolve_profile() {
+            Some(profile) => {
+                let pairs: Vec<(&str, &str)> =
+                    vars.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+                profile.write_vars(&pairs).await?;
+                println!(
+                    "Applied to {:?}. Restart your shell (or 'source {:?}') for it to take effect.",
+                    profile.path, profile.path
+                );
+            }
+            None => {
+                println!("Could not detect your shell profile automatically; apply manually:");
+                print_manual_instructions(&vars, "export", "unset");
+            }
+        }
 
-        // We return Ok because we "handled" the request, even if we didn't write a file.
-        // This prevents the main loop from crashing or showing error.
         Ok(())
     }
 
     async fn restore(&self) -> Result<()> {
-        println!("To restore Brew configuration, please unset the environment variables:");
-        println!();
-        println!("    unset HOMEBREW_API_DOMAIN");
-        println!("    unset HOMEBREW_BOTTLE_DOMAIN");
-        println!();
-        println!("If you added these to your shell profile (~/.zshrc, ~/.bash_profile, etc.), please remove them manually.");
+        if self.dry_run {
+            println!("To restore Brew configuration, please unset the environment variables:");
+            println!();
+            println!("    unset HOMEBREW_API_DOMAIN");
+            println!("    unset HOMEBREW_BOTTLE_DOMAIN");
+            println!("    unset HOMEBREW_ARTIFACT_DOMAIN");
+            return Ok(());
+        }
+
+        match self.resolve_profile() {
+            Some(profile) => {
+                profile.remove_block().await?;
+                println!(
+                    "Removed the cmirror-managed block from {:?}. Restart your shell for it to take effect.",
+                    profile.path
+                );
+            }
+            None => {
+                println!("Could not detect your shell profile automatically; please unset these manually:");
+                println!();
+                println!("    unset HOMEBREW_API_DOMAIN");
+                println!("    unset HOMEBREW_BOTTLE_DOMAIN");
+                println!("    unset HOMEBREW_ARTIFACT_DOMAIN");
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn reset(&self) -> Result<()> {
+        // Brew mirrors are env vars, not a file cmirror manages, so there is
+        // no separate "true upstream default" to restore beyond unsetting them.
+        self.restore().await
+    }
+}
+
+/// `--dry-run` 或无法定位 shell profile 时的兜底：打印用户需要手动执行的命令
+fn print_manual_instructions(vars: &[(String, String)], set_verb: &str, unset_verb: &str) {
+    println!("To apply this mirror, please run the following commands in your terminal:");
+    println!();
+    for (var, value) in vars {
+        println!("    {} {}=\"{}\"", set_verb, var, value);
+    }
+    println!();
+    println!(
+        "To make it permanent, add the above lines to your ~/.zshrc or ~/.bash_profile (use '{}' to remove them later).",
+        unset_verb
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_set_source_writes_bottle_and_artifact_domains() -> Result<()> {
+        let dir = tempdir()?;
+        let profile_path = dir.path().join("profile.sh");
+        let manager = BrewManager::with_profile_path(false, profile_path.clone());
+
+        let mirror = Mirror {
+            endpoints: HashMap::from([
+                ("bottle".to_string(), "https://mirror.test/bottles".to_string()),
+                ("artifact".to_string(), "https://mirror.test/artifacts".to_string()),
+            ]),
+            ..Mirror::new("Test", "https://mirror.test/api")
+        };
+
+        manager.set_source(&mirror).await?;
+
+        let content = tokio::fs::read_to_string(&profile_path).await?;
+        assert!(content.contains("export HOMEBREW_API_DOMAIN=\"https://mirror.test/api\""));
+        assert!(content.contains("export HOMEBREW_BOTTLE_DOMAIN=\"https://mirror.test/bottles\""));
+        assert!(content.contains("export HOMEBREW_ARTIFACT_DOMAIN=\"https://mirror.test/artifacts\""));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_set_source_omits_endpoints_the_mirror_does_not_declare() -> Result<()> {
+        let dir = tempdir()?;
+        let profile_path = dir.path().join("profile.sh");
+        let manager = BrewManager::with_profile_path(false, profile_path.clone());
+
+        let mirror = Mirror::new("Official", "https://formulae.brew.sh/api");
+        manager.set_source(&mirror).await?;
+
+        let content = tokio::fs::read_to_string(&profile_path).await?;
+        assert!(content.contains("export HOMEBREW_API_DOMAIN=\"https://formulae.brew.sh/api\""));
+        assert!(!content.contains("HOMEBREW_BOTTLE_DOMAIN"));
+        assert!(!content.contains("HOMEBREW_ARTIFACT_DOMAIN"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_restore_removes_managed_block() -> Result<()> {
+        let dir = tempdir()?;
+        let profile_path = dir.path().join("profile.sh");
+        let manager = BrewManager::with_profile_path(false, profile_path.clone());
+
+        let mirror = Mirror {
+            endpoints: HashMap::from([(
+                "artifact".to_string(),
+                "https://mirror.test/artifacts".to_string(),
+            )]),
+            ..Mirror::new("Test", "https://mirror.test/api")
+        };
+        manager.set_source(&mirror).await?;
+        manager.restore().await?;
+
+        let content = tokio::fs::read_to_string(&profile_path).await?;
+        assert!(!content.contains("HOMEBREW_ARTIFACT_DOMAIN"));
+
         Ok(())
     }
 }
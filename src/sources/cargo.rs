@@ -35,10 +35,15 @@ impl SourceManager for CargoManager {
         false
     }
 
-    fn list_candidates(&self) -> Vec<Mirror> {
+    fn list_candidates(&self) -> Result<Vec<Mirror>> {
         config::get_candidates("cargo")
     }
 
+    fn probe_path(&self) -> &str {
+        // sparse 协议索引的根配置文件，任何 sparse 镜像都必须提供
+        "config.json"
+    }
+
     fn config_path(&self) -> PathBuf {
         if let Some(ref path) = self.custom_path {
             return path.clone();
@@ -94,9 +99,12 @@ impl SourceManager for CargoManager {
             String::new()
         };
 
-        // 3. Backup
+        // 3. Backup: the label describes the content being overwritten, not
+        // the incoming mirror
         if !content.is_empty() {
-            utils::backup_file(&path).await?;
+            let previous_url = self.current_url().await?;
+            let label = utils::resolve_backup_label(&self.list_candidates()?, previous_url.as_deref());
+            utils::backup_file(&path, label.as_deref()).await?;
         }
 
         // 4. Update TOML
@@ -156,6 +164,39 @@ impl SourceManager for CargoManager {
     async fn restore(&self) -> Result<()> {
         utils::restore_latest_backup(&self.config_path()).await
     }
+
+    async fn reset(&self) -> Result<()> {
+        let path = self.config_path();
+        if !fs::try_exists(&path).await.unwrap_or(false) {
+            return Ok(());
+        }
+
+        let content = fs::read_to_string(&path).await?;
+        utils::backup_file(&path, None).await?;
+
+        let mut config: toml::Value =
+            toml::from_str(&content).unwrap_or(toml::Value::Table(toml::map::Map::new()));
+
+        if let Some(root) = config.as_table_mut() {
+            if let Some(source) = root.get_mut("source").and_then(|s| s.as_table_mut()) {
+                source.remove("crates-io");
+                source.remove("mirror");
+                if source.is_empty() {
+                    root.remove("source");
+                }
+            }
+        }
+
+        let is_empty = config.as_table().map(|t| t.is_empty()).unwrap_or(true);
+        if is_empty {
+            fs::remove_file(&path).await?;
+        } else {
+            let new_content = toml::to_string_pretty(&config)?;
+            fs::write(&path, new_content).await?;
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -173,10 +214,7 @@ mod tests {
         assert!(manager.current_url().await?.is_none());
 
         // 2. Set source
-        let mirror = Mirror {
-            name: "TestCargo".to_string(),
-            url: "sparse+https://test.crates.io/index".to_string(),
-        };
+        let mirror = Mirror::new("TestCargo", "sparse+https://test.crates.io/index");
         manager.set_source(&mirror).await?;
 
         // 3. Check current
@@ -190,10 +228,7 @@ mod tests {
         assert!(content.contains(&format!("registry = \"{}\"", mirror.url)));
 
         // 4. Set another
-        let mirror2 = Mirror {
-            name: "TestCargo2".to_string(),
-            url: "sparse+https://test2.crates.io/index".to_string(),
-        };
+        let mirror2 = Mirror::new("TestCargo2", "sparse+https://test2.crates.io/index");
         tokio::time::sleep(std::time::Duration::from_secs(1)).await;
         manager.set_source(&mirror2).await?;
         assert_eq!(manager.current_url().await?, Some(mirror2.url.clone()));
@@ -204,4 +239,58 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_cargo_reset_clears_override_and_is_recoverable() -> Result<()> {
+        let dir = tempdir()?;
+        let config_path = dir.path().join("config.toml");
+        let manager = CargoManager::with_path(config_path.clone());
+
+        let mirror = Mirror::new("TestCargo", "sparse+https://test.crates.io/index");
+        manager.set_source(&mirror).await?;
+        assert_eq!(manager.current_url().await?, Some(mirror.url.clone()));
+
+        // Reset removes the [source] override entirely; with nothing else in
+        // the file, config.toml itself is removed.
+        manager.reset().await?;
+        assert!(manager.current_url().await?.is_none());
+        assert!(!config_path.exists());
+
+        // reset() backs up what it removed, so it can still be rolled back.
+        manager.restore().await?;
+        assert_eq!(manager.current_url().await?, Some(mirror.url));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cargo_reset_on_missing_file_is_a_noop() -> Result<()> {
+        let dir = tempdir()?;
+        let config_path = dir.path().join("config.toml");
+        let manager = CargoManager::with_path(config_path.clone());
+
+        manager.reset().await?;
+        assert!(!config_path.exists());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cargo_plain_git_registry() -> Result<()> {
+        // Not every mirror speaks the sparse protocol; a plain git registry
+        // URL (no "sparse+" prefix) should round-trip untouched too.
+        let dir = tempdir()?;
+        let config_path = dir.path().join("config.toml");
+        let manager = CargoManager::with_path(config_path.clone());
+
+        let mirror = Mirror::new("GitMirror", "https://git.example.com/crates-io-index.git");
+        manager.set_source(&mirror).await?;
+
+        assert_eq!(manager.current_url().await?, Some(mirror.url.clone()));
+
+        let content = fs::read_to_string(&config_path).await?;
+        assert!(content.contains(&format!("registry = \"{}\"", mirror.url)));
+
+        Ok(())
+    }
 }
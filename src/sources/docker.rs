@@ -1,7 +1,7 @@
 use crate::config;
 use crate::error::Result;
 use crate::traits::SourceManager;
-use crate::types::Mirror;
+use crate::types::{Diagnostic, Mirror};
 use crate::utils;
 use async_trait::async_trait;
 use directories::BaseDirs;
@@ -27,10 +27,15 @@ impl SourceManager for DockerManager {
         true
     }
 
-    fn list_candidates(&self) -> Vec<Mirror> {
+    fn list_candidates(&self) -> Result<Vec<Mirror>> {
         config::get_candidates("docker")
     }
 
+    fn probe_path(&self) -> &str {
+        // Registry v2 API 的 ping 端点，任何符合规范的 registry 都会响应
+        "v2/"
+    }
+
     fn config_path(&self) -> PathBuf {
         if cfg!(target_os = "windows") {
             PathBuf::from(r"C:\ProgramData\docker\config\daemon.json")
@@ -85,9 +90,11 @@ impl SourceManager for DockerManager {
             serde_json::json!({})
         };
 
-        // 2. 备份
+        // 2. 备份：标签对应被覆盖的旧内容，而不是马上写入的新 mirror
 
-        utils::backup_file(&path).await?;
+        let previous_url = self.current_url().await?;
+        let label = utils::resolve_backup_label(&self.list_candidates()?, previous_url.as_deref());
+        utils::backup_file(&path, label.as_deref()).await?;
 
         // 3. 修改 registry-mirrors 字段
 
@@ -107,4 +114,91 @@ impl SourceManager for DockerManager {
     async fn restore(&self) -> Result<()> {
         utils::restore_latest_backup(&self.config_path()).await
     }
+
+    async fn reset(&self) -> Result<()> {
+        let path = self.config_path();
+        if !fs::try_exists(&path).await.unwrap_or(false) {
+            return Ok(());
+        }
+
+        let content = fs::read_to_string(&path).await?;
+        utils::backup_file(&path, None).await?;
+
+        let mut config: Value = serde_json::from_str(&content).unwrap_or(serde_json::json!({}));
+        if let Some(obj) = config.as_object_mut() {
+            obj.remove("registry-mirrors");
+        }
+
+        if config.as_object().map(|o| o.is_empty()).unwrap_or(true) {
+            fs::remove_file(&path).await?;
+        } else {
+            let new_content = serde_json::to_string_pretty(&config)?;
+            fs::write(&path, new_content).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn check(&self) -> Result<Vec<Diagnostic>> {
+        let mut diagnostics = Vec::new();
+        let path = self.config_path();
+
+        if !fs::try_exists(&path).await.unwrap_or(false) {
+            return Ok(diagnostics);
+        }
+
+        let content = fs::read_to_string(&path).await?;
+        let v: Value = serde_json::from_str(&content)?;
+
+        let Some(mirrors) = v.get("registry-mirrors").and_then(|v| v.as_array()) else {
+            return Ok(diagnostics);
+        };
+
+        let mut seen = std::collections::HashSet::new();
+        for entry in mirrors {
+            let Some(url) = entry.as_str() else {
+                diagnostics.push(
+                    Diagnostic::error(format!(
+                        "daemon.json 'registry-mirrors' entry is not a string: {}",
+                        entry
+                    ))
+                    .with_file(path.clone()),
+                );
+                continue;
+            };
+
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                diagnostics.push(
+                    Diagnostic::error(format!(
+                        "daemon.json 'registry-mirrors' entry is malformed (expected http(s)://): {}",
+                        url
+                    ))
+                    .with_file(path.clone()),
+                );
+                continue;
+            }
+
+            if url.starts_with("http://") {
+                diagnostics.push(
+                    Diagnostic::warning(format!(
+                        "docker is using an insecure http:// registry mirror: {}",
+                        url
+                    ))
+                    .with_file(path.clone()),
+                );
+            }
+
+            if !seen.insert(url.to_string()) {
+                diagnostics.push(
+                    Diagnostic::warning(format!(
+                        "daemon.json 'registry-mirrors' has a duplicate entry: {}",
+                        url
+                    ))
+                    .with_file(path.clone()),
+                );
+            }
+        }
+
+        Ok(diagnostics)
+    }
 }
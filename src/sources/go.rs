@@ -14,6 +14,76 @@ impl GoManager {
     }
 }
 
+/// 读取单个 `go env` 变量，未安装 go 或命令失败时视为未设置
+async fn go_env_get(key: &str) -> Result<Option<String>> {
+    let output = Command::new("go").args(["env", key]).output().await;
+
+    match output {
+        Ok(o) if o.status.success() => {
+            let stdout = String::from_utf8_lossy(&o.stdout).trim().to_string();
+            if stdout.is_empty() {
+                Ok(None)
+            } else {
+                Ok(Some(stdout))
+            }
+        }
+        _ => Ok(None),
+    }
+}
+
+/// 通过 `go env -w k1=v1 k2=v2 ...` 一次性写入多个变量
+async fn go_env_write(pairs: &[(&str, &str)]) -> Result<()> {
+    let mut args = vec!["env".to_string(), "-w".to_string()];
+    args.extend(pairs.iter().map(|(k, v)| format!("{}={}", k, v)));
+
+    let status = Command::new("go").args(&args).status().await?;
+    if !status.success() {
+        return Err(MirrorError::Custom(
+            "Failed to set go env vars via 'go env -w'".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// 通过 `go env -u k1 k2 ...` 一次性取消设置多个变量
+async fn go_env_unset(keys: &[&str]) -> Result<()> {
+    let mut args = vec!["env".to_string(), "-u".to_string()];
+    args.extend(keys.iter().map(|s| s.to_string()));
+
+    let status = Command::new("go").args(&args).status().await?;
+    if !status.success() {
+        return Err(MirrorError::Custom(
+            "Failed to unset go env vars via 'go env -u'".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// 把 GOPROXY 的值拆成逐项列表，保留 "direct"/"off" 终止符
+fn parse_proxy_chain(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// 把 `mirror_url` 放到链条最前面：链条中已有的同一条目会被去重，其余条目
+/// (包括用户自己添加的中间代理) 原样保留在原来的相对顺序；如果链条里还没有
+/// `direct`/`off` 终止符，补上一个 `direct`，和旧版单一条目写入时的行为一致。
+fn build_proxy_chain(existing: &[String], mirror_url: &str) -> String {
+    let mut chain = vec![mirror_url.to_string()];
+    for entry in existing {
+        if entry != mirror_url {
+            chain.push(entry.clone());
+        }
+    }
+    if !chain.iter().any(|e| e == "direct" || e == "off") {
+        chain.push("direct".to_string());
+    }
+    chain.join(",")
+}
+
 #[async_trait]
 impl SourceManager for GoManager {
     fn name(&self) -> &'static str {
@@ -24,7 +94,7 @@ impl SourceManager for GoManager {
         false
     }
 
-    fn list_candidates(&self) -> Vec<Mirror> {
+    fn list_candidates(&self) -> Result<Vec<Mirror>> {
         config::get_candidates("go")
     }
 
@@ -36,56 +106,124 @@ impl SourceManager for GoManager {
     }
 
     async fn current_url(&self) -> Result<Option<String>> {
-        // Use `go env GOPROXY` to get the current value
-        let output = Command::new("go").args(["env", "GOPROXY"]).output().await;
-
-        match output {
-            Ok(o) if o.status.success() => {
-                let stdout = String::from_utf8_lossy(&o.stdout).trim().to_string();
-                if stdout.is_empty() {
-                    Ok(None)
-                } else {
-                    // Usually returns "https://proxy.golang.org,direct"
-                    // We might want to split by comma and take the first one?
-                    let first = stdout.split(',').next().unwrap_or(&stdout).to_string();
-                    Ok(Some(first))
-                }
+        // GOPROXY 通常是一整条逗号分隔的链 (如 "https://proxy.golang.org,direct")；
+        // 这里只取第一项作为 "当前源" 用于展示/匹配候选，完整链条在 set_source 中
+        // 整体保留，不会因为读取时只看第一项而丢失后面的条目。
+        match go_env_get("GOPROXY").await? {
+            Some(value) => {
+                let chain = parse_proxy_chain(&value);
+                Ok(chain.into_iter().next())
             }
-            _ => Ok(None), // Go not installed or error
+            None => Ok(None),
         }
     }
 
     async fn set_source(&self, mirror: &Mirror) -> Result<()> {
-        // Use `go env -w GOPROXY=...`
-        // Append ",direct" to ensure fallback works for private modules
-        let new_val = format!("{},direct", mirror.url);
-
-        let status = Command::new("go")
-            .args(["env", "-w", &format!("GOPROXY={}", new_val)])
-            .status()
-            .await?;
-
-        if !status.success() {
-            return Err(MirrorError::Custom(
-                "Failed to set GOPROXY via 'go env -w'".to_string(),
-            ));
+        let existing = go_env_get("GOPROXY").await?.unwrap_or_default();
+        let existing_chain = parse_proxy_chain(&existing);
+        let new_proxy = build_proxy_chain(&existing_chain, &mirror.url);
+
+        let mut pairs: Vec<(&str, &str)> = vec![("GOPROXY", new_proxy.as_str())];
+
+        // 一些镜像 (如 goproxy.cn) 同时提供校验和数据库代理，declare 了就一并设置，
+        // 避免 GOSUMDB 仍指向默认地址导致在受限网络下校验失败。
+        if let Some(sumdb) = mirror.endpoint("sumdb") {
+            pairs.push(("GOSUMDB", sumdb));
+        }
+        if let Some(nosumcheck) = mirror.endpoint("nosumcheck") {
+            pairs.push(("GONOSUMCHECK", nosumcheck));
+        }
+        if let Some(private) = mirror.endpoint("private") {
+            pairs.push(("GOPRIVATE", private));
         }
 
-        Ok(())
+        go_env_write(&pairs).await
     }
 
     async fn restore(&self) -> Result<()> {
-        println!("Restoring GOPROXY to default (unsetting)...");
-        let status = Command::new("go")
-            .args(["env", "-u", "GOPROXY"])
-            .status()
-            .await?;
-
-        if !status.success() {
-            return Err(MirrorError::Custom(
-                "Failed to unset GOPROXY via 'go env -u'".to_string(),
-            ));
-        }
-        Ok(())
+        println!("Restoring Go proxy settings to default (unsetting)...");
+        go_env_unset(&["GOPROXY", "GOSUMDB", "GONOSUMCHECK", "GOPRIVATE"]).await
+    }
+
+    async fn reset(&self) -> Result<()> {
+        // GOPROXY is not backed by a file cmirror manages, so there is no
+        // "true upstream default" distinct from unsetting the override.
+        self.restore().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proxy_chain_splits_and_trims_entries() {
+        assert_eq!(
+            parse_proxy_chain("https://a.example.com, https://b.example.com ,direct"),
+            vec!["https://a.example.com", "https://b.example.com", "direct"]
+        );
+    }
+
+    #[test]
+    fn test_parse_proxy_chain_empty_value_yields_empty_chain() {
+        assert!(parse_proxy_chain("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_proxy_chain_ignores_empty_segments() {
+        // A trailing/doubled comma shouldn't produce a bogus empty entry.
+        assert_eq!(
+            parse_proxy_chain("https://a.example.com,,direct,"),
+            vec!["https://a.example.com", "direct"]
+        );
+    }
+
+    #[test]
+    fn test_build_proxy_chain_on_empty_chain_appends_direct() {
+        assert_eq!(
+            build_proxy_chain(&[], "https://goproxy.cn"),
+            "https://goproxy.cn,direct"
+        );
+    }
+
+    #[test]
+    fn test_build_proxy_chain_preserves_existing_direct_terminator() {
+        let existing = parse_proxy_chain("https://old.example.com,direct");
+        assert_eq!(
+            build_proxy_chain(&existing, "https://goproxy.cn"),
+            "https://goproxy.cn,https://old.example.com,direct"
+        );
+    }
+
+    #[test]
+    fn test_build_proxy_chain_preserves_existing_off_terminator() {
+        let existing = parse_proxy_chain("https://old.example.com,off");
+        assert_eq!(
+            build_proxy_chain(&existing, "https://goproxy.cn"),
+            "https://goproxy.cn,https://old.example.com,off"
+        );
+    }
+
+    #[test]
+    fn test_build_proxy_chain_dedups_reapplied_mirror() {
+        // Re-applying the same mirror that is already first in the chain
+        // must not duplicate it.
+        let existing = parse_proxy_chain("https://goproxy.cn,direct");
+        assert_eq!(
+            build_proxy_chain(&existing, "https://goproxy.cn"),
+            "https://goproxy.cn,direct"
+        );
+    }
+
+    #[test]
+    fn test_build_proxy_chain_moves_mirror_to_front_keeping_rest() {
+        // Switching to a mirror that is already present further down the
+        // chain should move it to the front and keep the other entries,
+        // instead of duplicating it.
+        let existing = parse_proxy_chain("https://old.example.com,https://goproxy.cn,direct");
+        assert_eq!(
+            build_proxy_chain(&existing, "https://goproxy.cn"),
+            "https://goproxy.cn,https://old.example.com,direct"
+        );
     }
 }
@@ -12,17 +12,24 @@ use crate::error::{Result, MirrorError};
 pub const SUPPORTED_TOOLS: &[&str] = &["pip", "npm", "docker", "go", "cargo", "brew", "apt"];
 
 pub fn get_manager(name: &str) -> Result<Box<dyn SourceManager>> {
+    get_manager_with_options(name, false)
+}
+
+/// 与 `get_manager` 相同，但允许为支持该选项的 Manager (目前只有 `brew`，它
+/// 通过改写 shell 启动文件来持久化环境变量) 指定 `dry_run`：为 `true` 时只
+/// 打印将要执行的操作，不实际写入任何文件。其余 Manager 忽略该参数。
+pub fn get_manager_with_options(name: &str, dry_run: bool) -> Result<Box<dyn SourceManager>> {
     match name.to_lowercase().as_str() {
         "pip" => Ok(Box::new(pip::PipManager::new())),
         "docker" => Ok(Box::new(docker::DockerManager::new())),
         "npm" => Ok(Box::new(npm::NpmManager::new())),
         "go" => Ok(Box::new(go::GoManager::new())),
         "cargo" => Ok(Box::new(cargo::CargoManager::new())),
-        "brew" => Ok(Box::new(brew::BrewManager::new())),
+        "brew" => Ok(Box::new(brew::BrewManager::new(dry_run))),
         "apt" => Ok(Box::new(apt::AptManager::new())),
         _ => Err(MirrorError::UnknownTool(format!(
-            "Unsupported tool: '{}'. Available: {}", 
-            name, 
+            "Unsupported tool: '{}'. Available: {}",
+            name,
             SUPPORTED_TOOLS.join(", ")
         ))),
     }
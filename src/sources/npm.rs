@@ -36,10 +36,15 @@ impl SourceManager for NpmManager {
         false
     }
 
-    fn list_candidates(&self) -> Vec<Mirror> {
+    fn list_candidates(&self) -> Result<Vec<Mirror>> {
         config::get_candidates("npm")
     }
 
+    fn probe_path(&self) -> &str {
+        // npm 自身包的元数据文档体积小且每个 registry 都有
+        "npm"
+    }
+
     fn config_path(&self) -> PathBuf {
         if let Some(ref path) = self.custom_path {
             return path.clone();
@@ -82,9 +87,12 @@ impl SourceManager for NpmManager {
             String::new()
         };
 
-        // 3. Backup using generic utility
+        // 3. Backup using generic utility: label the content being
+        // overwritten, not the incoming mirror
         if !content.is_empty() {
-            utils::backup_file(&path).await?;
+            let previous_url = self.current_url().await?;
+            let label = utils::resolve_backup_label(&self.list_candidates()?, previous_url.as_deref());
+            utils::backup_file(&path, label.as_deref()).await?;
         }
 
         // 4. Update content
@@ -110,6 +118,27 @@ impl SourceManager for NpmManager {
     async fn restore(&self) -> Result<()> {
         utils::restore_latest_backup(&self.config_path()).await
     }
+
+    async fn reset(&self) -> Result<()> {
+        let path = self.config_path();
+        if !fs::try_exists(&path).await.unwrap_or(false) {
+            return Ok(());
+        }
+
+        let content = fs::read_to_string(&path).await?;
+        utils::backup_file(&path, None).await?;
+
+        let re = Regex::new(r"(?m)^registry\s*=\s*.*\n?")?;
+        let new_content = re.replace(&content, "").to_string();
+
+        if new_content.trim().is_empty() {
+            fs::remove_file(&path).await?;
+        } else {
+            fs::write(&path, new_content).await?;
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -127,10 +156,7 @@ mod tests {
         assert!(manager.current_url().await?.is_none());
 
         // 2. Set source
-        let mirror = Mirror {
-            name: "TestNpm".to_string(),
-            url: "https://registry.npm.test.org/".to_string(),
-        };
+        let mirror = Mirror::new("TestNpm", "https://registry.npm.test.org/");
         manager.set_source(&mirror).await?;
 
         // 3. Check current
@@ -140,10 +166,7 @@ mod tests {
         assert!(content.contains(&format!("registry={}", mirror.url)));
 
         // 4. Set another
-        let mirror2 = Mirror {
-            name: "TestNpm2".to_string(),
-            url: "https://registry.npm.test2.org/".to_string(),
-        };
+        let mirror2 = Mirror::new("TestNpm2", "https://registry.npm.test2.org/");
         tokio::time::sleep(std::time::Duration::from_secs(1)).await;
         manager.set_source(&mirror2).await?;
         assert_eq!(manager.current_url().await?, Some(mirror2.url.clone()));
@@ -154,4 +177,36 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_npm_reset_clears_override_and_is_recoverable() -> Result<()> {
+        let dir = tempdir()?;
+        let config_path = dir.path().join(".npmrc");
+        let manager = NpmManager::with_path(config_path.clone());
+
+        let mirror = Mirror::new("TestNpm", "https://registry.npm.test.org/");
+        manager.set_source(&mirror).await?;
+        assert_eq!(manager.current_url().await?, Some(mirror.url.clone()));
+
+        manager.reset().await?;
+        assert!(manager.current_url().await?.is_none());
+
+        // reset() backs up what it removed, so it can still be rolled back.
+        manager.restore().await?;
+        assert_eq!(manager.current_url().await?, Some(mirror.url));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_npm_reset_on_missing_file_is_a_noop() -> Result<()> {
+        let dir = tempdir()?;
+        let config_path = dir.path().join(".npmrc");
+        let manager = NpmManager::with_path(config_path.clone());
+
+        manager.reset().await?;
+        assert!(!config_path.exists());
+
+        Ok(())
+    }
 }
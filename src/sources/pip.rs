@@ -1,7 +1,7 @@
 use crate::config;
 use crate::error::Result;
 use crate::traits::SourceManager;
-use crate::types::Mirror;
+use crate::types::{Diagnostic, Mirror};
 use crate::utils;
 use async_trait::async_trait;
 use directories::BaseDirs;
@@ -36,10 +36,16 @@ impl SourceManager for PipManager {
         false
     }
 
-    fn list_candidates(&self) -> Vec<Mirror> {
+    fn list_candidates(&self) -> Result<Vec<Mirror>> {
         config::get_candidates("pip")
     }
 
+    fn probe_path(&self) -> &str {
+        // 镜像 URL 本身已指向 simple index (如 ".../simple")，这里取其下保证存在的
+        // pip 项目小页面，避免探测根路径时 404
+        "pip/"
+    }
+
     fn config_path(&self) -> PathBuf {
         if let Some(ref path) = self.custom_path {
             return path.clone();
@@ -96,9 +102,12 @@ impl SourceManager for PipManager {
             String::new()
         };
 
-        // 3. 备份 (如果文件存在且不为空)
+        // 3. 备份 (如果文件存在且不为空)：标签对应的是即将被覆盖的旧内容，
+        //    不是马上要写入的 mirror
         if !content.is_empty() {
-            utils::backup_file(&path).await?;
+            let previous_url = self.current_url().await?;
+            let label = utils::resolve_backup_label(&self.list_candidates()?, previous_url.as_deref());
+            utils::backup_file(&path, label.as_deref()).await?;
         }
 
         // 4. 构造新内容 (使用正则替换，保留其他配置)
@@ -128,6 +137,65 @@ impl SourceManager for PipManager {
     async fn restore(&self) -> Result<()> {
         utils::restore_latest_backup(&self.config_path()).await
     }
+
+    async fn reset(&self) -> Result<()> {
+        let path = self.config_path();
+        if !fs::try_exists(&path).await.unwrap_or(false) {
+            return Ok(());
+        }
+
+        let content = fs::read_to_string(&path).await?;
+        utils::backup_file(&path, None).await?;
+
+        let re = Regex::new(r"(?m)^index-url\s*=\s*.*\n?")?;
+        let new_content = re.replace(&content, "").to_string();
+
+        if new_content.trim().is_empty() {
+            fs::remove_file(&path).await?;
+        } else {
+            fs::write(&path, new_content).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn check(&self) -> Result<Vec<Diagnostic>> {
+        let mut diagnostics = Vec::new();
+        let path = self.config_path();
+
+        if !fs::try_exists(&path).await.unwrap_or(false) {
+            return Ok(diagnostics);
+        }
+
+        let content = fs::read_to_string(&path).await?;
+        let re = Regex::new(r"(?m)^index-url\s*=\s*(.+)$")?;
+        let urls: Vec<&str> = re
+            .captures_iter(&content)
+            .map(|caps| caps.get(1).unwrap().as_str().trim())
+            .collect();
+
+        if urls.len() > 1 {
+            diagnostics.push(
+                Diagnostic::error(format!(
+                    "pip.conf has {} conflicting 'index-url' lines: {}",
+                    urls.len(),
+                    urls.join(", ")
+                ))
+                .with_file(path.clone()),
+            );
+        }
+
+        for url in urls {
+            if url.starts_with("http://") {
+                diagnostics.push(
+                    Diagnostic::warning(format!("pip is using an insecure http:// mirror: {}", url))
+                        .with_file(path.clone()),
+                );
+            }
+        }
+
+        Ok(diagnostics)
+    }
 }
 
 #[cfg(test)]
@@ -145,10 +213,7 @@ mod tests {
         assert!(manager.current_url().await?.is_none());
 
         // 2. Set source
-        let mirror = Mirror {
-            name: "Test".to_string(),
-            url: "https://test.pypi.org/simple".to_string(),
-        };
+        let mirror = Mirror::new("Test", "https://test.pypi.org/simple");
         manager.set_source(&mirror).await?;
 
         // 3. Check current url
@@ -161,10 +226,7 @@ mod tests {
         assert!(content.contains(&format!("index-url = {}", mirror.url)));
 
         // 5. Set another source (Backup should be created)
-        let mirror2 = Mirror {
-            name: "Test2".to_string(),
-            url: "https://test2.pypi.org/simple".to_string(),
-        };
+        let mirror2 = Mirror::new("Test2", "https://test2.pypi.org/simple");
         // Sleep a bit to ensure timestamp diff if backup naming relies on second precision
         // (Our utils uses seconds, so we might overwrite backup if too fast?
         // utils::backup_file uses SystemTime::now()...as_secs(). If running super fast, timestamp might be same.
@@ -183,4 +245,114 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_pip_check_flags_duplicates_and_insecure_url() -> Result<()> {
+        let dir = tempdir()?;
+        let config_path = dir.path().join("pip.conf");
+        let content = "[global]\n\
+index-url = http://mirrors.test.com/simple\n\
+index-url = https://other.test.com/simple\n";
+        fs::write(&config_path, content).await?;
+
+        let manager = PipManager::with_path(config_path.clone());
+        let diagnostics = manager.check().await?;
+
+        assert!(diagnostics.iter().any(|d| d.message.contains("conflicting")));
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.message.contains("insecure http://")));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pip_check_clean_config_has_no_diagnostics() -> Result<()> {
+        let dir = tempdir()?;
+        let config_path = dir.path().join("pip.conf");
+        let manager = PipManager::with_path(config_path.clone());
+
+        let mirror = Mirror::new("Test", "https://test.pypi.org/simple");
+        manager.set_source(&mirror).await?;
+
+        assert!(manager.check().await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pip_reset_clears_override_and_is_recoverable() -> Result<()> {
+        let dir = tempdir()?;
+        let config_path = dir.path().join("pip.conf");
+        let manager = PipManager::with_path(config_path.clone());
+
+        let mirror = Mirror::new("Test", "https://test.pypi.org/simple");
+        manager.set_source(&mirror).await?;
+        assert_eq!(manager.current_url().await?, Some(mirror.url.clone()));
+
+        // Reset removes the override entirely, back to pip's implicit default.
+        manager.reset().await?;
+        assert!(manager.current_url().await?.is_none());
+
+        // reset() backs up what it removed, so it can still be rolled back.
+        manager.restore().await?;
+        assert_eq!(manager.current_url().await?, Some(mirror.url));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pip_reset_on_missing_file_is_a_noop() -> Result<()> {
+        let dir = tempdir()?;
+        let config_path = dir.path().join("pip.conf");
+        let manager = PipManager::with_path(config_path.clone());
+
+        // Resetting before anything was ever configured should not error or
+        // create a file out of thin air.
+        manager.reset().await?;
+        assert!(!config_path.exists());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pip_backup_registry_history_and_explicit_rollback() -> Result<()> {
+        let dir = tempdir()?;
+        let config_path = dir.path().join("pip.conf");
+        let manager = PipManager::with_path(config_path.clone());
+
+        let mirror1 = Mirror::new("First", "https://first.test.com/simple");
+        manager.set_source(&mirror1).await?;
+
+        // Re-applying the exact same mirror backs up First's content once; the
+        // following switch finds that content unchanged since the last backup
+        // and must not add a second entry, even with no delay between calls.
+        manager.set_source(&mirror1).await?;
+
+        let mirror2 = Mirror::new("Second", "https://second.test.com/simple");
+        manager.set_source(&mirror2).await?;
+
+        let mirror3 = Mirror::new("Third", "https://third.test.com/simple");
+        manager.set_source(&mirror3).await?;
+
+        // Backup entries are tagged with the identity of the content being
+        // preserved (the OUTGOING mirror), not the incoming one that is about
+        // to overwrite it. These test mirrors aren't in the builtin candidate
+        // list, so the label falls back to the outgoing mirror's URL.
+        // Sequence of content changes:
+        //   (no file) -> First [no backup: nothing existed yet]
+        //   First -> First     [backup of "First"'s content, tagged First's URL]
+        //   First -> Second    [content unchanged since the last backup: deduped]
+        //   Second -> Third    [backup of "Second"'s content, tagged Second's URL]
+        let backups = manager.list_backups().await?;
+        assert_eq!(backups.len(), 2);
+        assert_eq!(backups[0].mirror_name.as_deref(), Some(mirror1.url.as_str()));
+        assert_eq!(backups[1].mirror_name.as_deref(), Some(mirror2.url.as_str()));
+
+        // Roll back to the first backup specifically, not just the latest.
+        manager.restore_backup(&backups[0].id).await?;
+        assert_eq!(manager.current_url().await?, Some(mirror1.url));
+
+        Ok(())
+    }
 }
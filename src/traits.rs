@@ -1,6 +1,7 @@
 use async_trait::async_trait;
-use crate::error::Result;
-use crate::types::Mirror;
+use crate::error::{MirrorError, Result};
+use crate::types::{BackupEntry, Diagnostic, Mirror};
+use crate::utils;
 use std::path::PathBuf;
 
 /// SourceManager: 所有镜像源管理模块必须实现的接口
@@ -12,8 +13,16 @@ pub trait SourceManager: Sync + Send {
     /// 是否需要 Root 权限 (如 apt, docker 需要 sudo)
     fn requires_sudo(&self) -> bool;
 
-    /// 获取内置的推荐源列表
-    fn list_candidates(&self) -> Vec<Mirror>;
+    /// 获取内置的推荐源列表，按 "环境变量 > 用户配置 > 内置默认" 分层合并
+    /// (见 `config::Config::get`)。用户配置文件存在但解析失败时返回 `Err`。
+    fn list_candidates(&self) -> Result<Vec<Mirror>>;
+
+    /// 测速时用于探测的相对路径：必须是该镜像上保证存在的小对象，
+    /// 避免像裸根路径那样在很多镜像上直接 404 (从而被误判为超时)。
+    /// 默认探测根路径，各 Manager 按工具特性覆盖。
+    fn probe_path(&self) -> &str {
+        ""
+    }
 
     /// 获取当前正在使用的源 URL
     /// 返回 Option: 如果未配置或无法解析，则返回 None (视为默认)
@@ -30,4 +39,213 @@ pub trait SourceManager: Sync + Send {
 
     /// 恢复到上一次的配置 (或默认配置)
     async fn restore(&self) -> Result<()>;
+
+    /// 重置为官方默认源，彻底移除 cmirror 写入的镜像覆盖。
+    /// 与 `restore` 不同：即使从未创建过备份，也能定位到该工具真正的
+    /// 上游默认值，而不是仅仅回退到上一次写入前的状态。
+    async fn reset(&self) -> Result<()>;
+
+    /// 并发测速所有候选源，返回排名第一的可达源 (按延迟分档、同档按吞吐量，
+    /// 见 `utils::benchmark_mirrors_with_client`)。
+    /// 候选列表为空时返回 `Ok(None)`；有候选但全部不可达时返回
+    /// `MirrorError::Custom`，而不是静默吞掉错误。
+    async fn fastest_candidate(&self) -> Result<Option<Mirror>> {
+        self.fastest_candidate_with_client(&utils::build_http_client())
+            .await
+    }
+
+    /// 同 `fastest_candidate`，但允许调用方注入探测用的 HTTP Client。
+    /// 测试可以传入指向本地 mock server 的 client 来稳定地模拟延迟差异，
+    /// 而不必依赖真实网络。
+    async fn fastest_candidate_with_client(
+        &self,
+        client: &reqwest::Client,
+    ) -> Result<Option<Mirror>> {
+        let candidates = self.list_candidates()?;
+        if candidates.is_empty() {
+            return Ok(None);
+        }
+
+        let results =
+            utils::benchmark_mirrors_with_client(client, candidates, self.probe_path()).await;
+
+        match results.into_iter().find(|r| r.latency_ms < u64::MAX) {
+            Some(r) => Ok(Some(r.mirror)),
+            None => Err(MirrorError::Custom(
+                "All candidate mirrors were unreachable.".to_string(),
+            )),
+        }
+    }
+
+    /// 只读地检查当前配置是否存在已知问题，不做任何修改。
+    /// 默认实现只做一个通用检查：当前源是否是不安全的 `http://`；
+    /// 各 Manager 可以覆盖以提供更细致的检查 (重复条目、格式错误等)。
+    async fn check(&self) -> Result<Vec<Diagnostic>> {
+        let mut diagnostics = Vec::new();
+
+        if let Some(url) = self.current_url().await? {
+            if url.starts_with("http://") {
+                diagnostics.push(
+                    Diagnostic::warning(format!(
+                        "{} is using an insecure http:// mirror: {}",
+                        self.name(),
+                        url
+                    ))
+                    .with_file(self.config_path()),
+                );
+            }
+        }
+
+        Ok(diagnostics)
+    }
+
+    /// 列出该 Manager 配置文件的全部历史备份 (按时间顺序)。
+    /// 默认基于 `config_path()`；管理多个文件的 Manager (如 apt 的 deb822 模式)
+    /// 应当覆盖此方法。
+    async fn list_backups(&self) -> Result<Vec<BackupEntry>> {
+        utils::list_backups(&self.config_path()).await
+    }
+
+    /// 回滚到 `list_backups` 返回的某一条具体记录，而不仅仅是最近一次。
+    async fn restore_backup(&self, id: &str) -> Result<()> {
+        utils::restore_backup(&self.config_path(), id).await
+    }
+
+    /// `fastest_candidate` 的别名，对应 `cmirror bench`/`--auto` 这一组命令行入口。
+    /// 两者语义完全一致，单独保留是为了让调用方可以用更贴近 "bench" 语境的名字。
+    async fn fastest(&self) -> Result<Option<Mirror>> {
+        self.fastest_candidate().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// 启动一个本地 mock server：每个连接在等待 `delay` 之后返回固定的 200 OK，
+    /// 用来在不依赖真实网络的情况下制造可控的延迟差异。
+    async fn spawn_mock_mirror(delay: Duration) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    break;
+                };
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 1024];
+                    let _ = stream.read(&mut buf).await;
+                    tokio::time::sleep(delay).await;
+                    let body = "ok";
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+
+        format!("http://{}", addr)
+    }
+
+    /// 仅用于测试的 SourceManager：候选列表是构造时直接给定的固定值
+    struct FakeManager {
+        candidates: Vec<Mirror>,
+    }
+
+    #[async_trait]
+    impl SourceManager for FakeManager {
+        fn name(&self) -> &'static str {
+            "fake"
+        }
+
+        fn requires_sudo(&self) -> bool {
+            false
+        }
+
+        fn list_candidates(&self) -> Result<Vec<Mirror>> {
+            Ok(self.candidates.clone())
+        }
+
+        async fn current_url(&self) -> Result<Option<String>> {
+            Ok(None)
+        }
+
+        async fn set_source(&self, _mirror: &Mirror) -> Result<()> {
+            Ok(())
+        }
+
+        fn config_path(&self) -> PathBuf {
+            PathBuf::from("fake")
+        }
+
+        async fn restore(&self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn reset(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fastest_candidate_picks_lower_latency_mirror() {
+        let fast_url = spawn_mock_mirror(Duration::from_millis(0)).await;
+        let slow_url = spawn_mock_mirror(Duration::from_millis(150)).await;
+
+        let manager = FakeManager {
+            candidates: vec![
+                Mirror::new("Slow", &slow_url),
+                Mirror::new("Fast", &fast_url),
+            ],
+        };
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(3))
+            .build()
+            .unwrap();
+
+        let winner = manager
+            .fastest_candidate_with_client(&client)
+            .await
+            .unwrap()
+            .expect("at least one candidate should be reachable");
+
+        assert_eq!(winner.name, "Fast");
+    }
+
+    #[tokio::test]
+    async fn test_fastest_candidate_empty_candidates_returns_none() {
+        let manager = FakeManager { candidates: vec![] };
+        let client = reqwest::Client::builder().build().unwrap();
+
+        assert!(manager
+            .fastest_candidate_with_client(&client)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fastest_candidate_all_unreachable_errors() {
+        // Nothing is listening on this port, so every probe should fail.
+        let manager = FakeManager {
+            candidates: vec![Mirror::new("Dead", "http://127.0.0.1:1")],
+        };
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(500))
+            .build()
+            .unwrap();
+
+        assert!(manager
+            .fastest_candidate_with_client(&client)
+            .await
+            .is_err());
+    }
 }
\ No newline at end of file
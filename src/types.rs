@@ -1,10 +1,19 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
 
 /// 镜像源定义
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Mirror {
     pub name: String,   // 例如: "Aliyun"
     pub url: String,    // 例如: "https://mirrors.aliyun.com/pypi/simple/"
+
+    /// 少数工具需要的附加端点 (brew：除了 formula API 之外，镜像站往往还分别
+    /// 提供传统 bottle 镜像和 ghcr.io OCI 制品镜像；go：校验和数据库代理等)。
+    /// 键是工具自定义的端点名 (brew 用 "bottle"/"artifact"，go 用
+    /// "sumdb"/"nosumcheck"/"private")，大多数工具用不到，留空即可。
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub endpoints: HashMap<String, String>,
 }
 
 impl Mirror {
@@ -12,13 +21,83 @@ impl Mirror {
         Self {
             name: name.to_string(),
             url: url.to_string(),
+            endpoints: HashMap::new(),
         }
     }
+
+    /// 获取某个附加端点，找不到时返回 `None`
+    pub fn endpoint(&self, key: &str) -> Option<&str> {
+        self.endpoints.get(key).map(|s| s.as_str())
+    }
 }
 
 /// 测速结果
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct BenchmarkResult {
     pub mirror: Mirror,
-    pub latency_ms: u64, // 延迟 (毫秒), 若失败则设为 u64::MAX
+    pub latency_ms: u64,       // 延迟 (TTFB, 毫秒), 若全部采样失败则设为 u64::MAX
+    pub throughput_kbps: u64,  // 吞吐量 (KB/s), 若全部采样失败则为 0
+}
+
+/// 诊断严重程度：`Warning` 仅提示，`Error` 应让 CLI 以非零状态码退出
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// `SourceManager::check` 产出的一条诊断信息
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub file: Option<PathBuf>,
+    pub line: Option<usize>,
+}
+
+impl Diagnostic {
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.into(),
+            file: None,
+            line: None,
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+            file: None,
+            line: None,
+        }
+    }
+
+    pub fn with_file(mut self, file: PathBuf) -> Self {
+        self.file = Some(file);
+        self
+    }
+
+    pub fn with_line(mut self, line: usize) -> Self {
+        self.line = Some(line);
+        self
+    }
+}
+
+/// 备份注册表中的一条记录。每次 `utils::backup_file` 成功备份都会写入一条，
+/// `id` 在同一份原始文件的备份目录内是单调递增且唯一的，可以用来精确回滚到
+/// 任意一次历史状态，而不仅仅是"最近一次"。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupEntry {
+    pub id: String,
+    pub original_path: PathBuf,
+    /// ISO 8601 时间戳 (UTC)，如 "2026-07-30T12:34:56Z"
+    pub timestamp: String,
+    /// 被备份内容所对应的镜像 (即被覆盖前的源) 名称，不在候选列表里时为其 URL；
+    /// 在 reset 等没有传入标签的场景下为 None
+    pub mirror_name: Option<String>,
+    /// 内容的简单哈希 (非密码学用途)，用于判断内容是否变化
+    pub content_hash: String,
 }
\ No newline at end of file
@@ -1,89 +1,257 @@
+use crate::config;
 use crate::error::{MirrorError, Result};
-use crate::types::{BenchmarkResult, Mirror};
+use crate::types::{BackupEntry, BenchmarkResult, Mirror};
 use indicatif::{ProgressBar, ProgressStyle};
 use reqwest::Client;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::fs;
 
 // 设置全局请求超时，防止慢源阻塞整个流程太久
 const REQUEST_TIMEOUT: u64 = 3;
 
-/// 备份文件 (如果有)
-/// 文件名格式: original.ext -> original.ext.bak.TIMESTAMP
-pub async fn backup_file(path: &Path) -> Result<()> {
-    if fs::try_exists(path).await.unwrap_or(false) {
-        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
-        // 这里的命名策略：如果有扩展名，插在扩展名后面还是直接追加？
-        // 之前的实现是: path.with_extension(format!("npmrc.bak.{}", timestamp))
-        // 这实际上是替换了扩展名。
-        // 更好的做法通常是直接在文件名后面追加 .bak.timestamp，保留原扩展名信息
-        // 但为了保持和之前代码行为的一致性 (或者优化它)，这里我选择直接追加后缀
-        // 例如: config.json -> config.json.bak.123456
-        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
-        let backup_name = format!("{}.bak.{}", file_name, timestamp);
-        let backup_path = path.with_file_name(backup_name);
-
-        fs::copy(path, &backup_path).await?;
-        println!("Backup created at: {:?}", backup_path);
+// 每个镜像采样的次数，取中位数以平滑抖动
+const PROBE_SAMPLES: usize = 3;
+
+// 排序时延迟档位的宽度：延迟按 latency / LATENCY_TOLERANCE_MS 分档，同一档内
+// 的差距被认为是噪声，让吞吐量而不是几毫秒的运气决定名次
+const LATENCY_TOLERANCE_MS: u64 = 50;
+
+/// 构建用于测速的 HTTP Client。
+///
+/// reqwest 默认就会读取 `HTTP_PROXY`/`HTTPS_PROXY`/`NO_PROXY` 环境变量，所以
+/// 受限网络下通常无需任何额外配置。`[network]` 配置段 (见
+/// `config::load_network_config`) 用于需要显式覆盖的场景：强制走某个代理、
+/// 信任私有 CA 证书，或（仅用于调试）跳过证书校验。
+pub(crate) fn build_http_client() -> Client {
+    let net_config = config::load_network_config();
+
+    let mut builder = Client::builder()
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT))
+        .redirect(reqwest::redirect::Policy::limited(5));
+
+    if let Some(proxy_url) = &net_config.proxy {
+        match reqwest::Proxy::all(proxy_url) {
+            Ok(proxy) => builder = builder.proxy(proxy),
+            Err(e) => eprintln!("Warning: invalid [network] proxy '{}': {}", proxy_url, e),
+        }
     }
-    Ok(())
+
+    if let Some(ca_path) = &net_config.ca_cert {
+        match std::fs::read(ca_path).and_then(|pem| {
+            reqwest::Certificate::from_pem(&pem)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+        }) {
+            Ok(cert) => builder = builder.add_root_certificate(cert),
+            Err(e) => eprintln!(
+                "Warning: could not load [network] ca_cert '{:?}': {}",
+                ca_path, e
+            ),
+        }
+    }
+
+    if net_config.danger_accept_invalid_certs.unwrap_or(false) {
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+
+    builder.build().unwrap_or_default()
 }
 
-/// 恢复到最近的备份
-pub async fn restore_latest_backup(path: &Path) -> Result<()> {
+// 备份注册表: 与原始文件同目录的一个隐藏子目录，保存每一次备份的内容
+// (`<id>.bak`) 及其元数据 (`<id>.meta.json`)。相比旧版 "original.ext.bak.TIMESTAMP"
+// 方案，这样可以在秒级时间戳冲突时不丢失历史记录，也能精确回滚到任意一条记录。
+fn backup_registry_dir(path: &Path) -> PathBuf {
     let parent = path.parent().unwrap_or_else(|| Path::new("."));
     let file_name = path.file_name().unwrap_or_default().to_string_lossy();
-    let prefix = format!("{}.bak.", file_name);
+    parent.join(format!(".{}.cmirror-backups", file_name))
+}
 
-    if !fs::try_exists(parent).await.unwrap_or(false) {
-        return Err(MirrorError::Custom(format!(
-            "Directory not found: {:?}",
-            parent
-        )));
+/// 非密码学用途的简单内容哈希 (FNV-1a 64-bit)，只用来判断两次写入内容是否相同
+fn content_hash(content: &[u8]) -> String {
+    const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    let mut hash = FNV_OFFSET;
+    for byte in content {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
     }
+    format!("{:016x}", hash)
+}
+
+/// 将 Unix 时间戳 (秒) 格式化为 UTC ISO 8601 字符串，不依赖额外的日期时间 crate
+/// (算法来自 Howard Hinnant 的 civil_from_days)
+fn iso_timestamp(secs: u64) -> String {
+    let days = secs as i64 / 86400;
+    let rem = secs as i64 % 86400;
+    let (hour, minute, second) = (rem / 3600, (rem % 3600) / 60, rem % 60);
+
+    let z = days + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = z - era * 146097; // [0, 146096]
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365; // [0, 399]
+    let y = yoe + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100); // [0, 365]
+    let mp = (5 * doy + 2) / 153; // [0, 11]
+    let d = doy - (153 * mp + 2) / 5 + 1; // [1, 31]
+    let m = if mp < 10 { mp + 3 } else { mp - 9 }; // [1, 12]
+    let y = if m <= 2 { y + 1 } else { y };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        y, m, d, hour, minute, second
+    )
+}
+
+/// 读取一个备份目录下的全部记录，按 id (数值) 升序排列
+async fn list_backup_entries(dir: &Path) -> Result<Vec<BackupEntry>> {
+    let mut out = Vec::new();
 
-    let mut entries = fs::read_dir(parent).await?;
-    let mut backups = Vec::new();
+    if !fs::try_exists(dir).await.unwrap_or(false) {
+        return Ok(out);
+    }
 
+    let mut entries = fs::read_dir(dir).await?;
     while let Some(entry) = entries.next_entry().await? {
-        let name = entry.file_name().to_string_lossy().to_string();
-        if name.starts_with(&prefix) {
-            backups.push(entry.path());
+        let path = entry.path();
+        if path.extension().map(|ext| ext == "json").unwrap_or(false) {
+            if let Ok(content) = fs::read_to_string(&path).await {
+                if let Ok(parsed) = serde_json::from_str::<BackupEntry>(&content) {
+                    out.push(parsed);
+                }
+            }
         }
     }
 
-    if backups.is_empty() {
-        return Err(MirrorError::Custom("No backup files found.".to_string()));
+    out.sort_by_key(|e| e.id.parse::<u64>().unwrap_or(0));
+    Ok(out)
+}
+
+/// 在覆盖写入前，为即将被顶替的旧内容解析出一个便于展示的标签。
+///
+/// 调用方应当传入覆盖前的 `current_url` (即将被备份保存的那份内容所对应的源)，
+/// 而不是马上要写入的新镜像 —— 否则备份记录里 "标签" 和 "内容" 会对不上号。
+/// 优先在候选列表里按 URL 匹配出镜像名称；如果当前源不在任何已知候选里
+/// (比如用户手工改过配置文件，或候选列表是测试用的临时数据)，退化为直接
+/// 使用 URL 本身作为标签，好过完全不知道这份备份是谁的。
+pub fn resolve_backup_label(candidates: &[Mirror], current_url: Option<&str>) -> Option<String> {
+    let url = current_url?;
+    let name = candidates
+        .iter()
+        .find(|c| c.url.trim_end_matches('/') == url.trim_end_matches('/'))
+        .map(|c| c.name.clone());
+    Some(name.unwrap_or_else(|| url.to_string()))
+}
+
+/// 备份文件 (如果存在)。`mirror_name` 记录被备份内容所对应的源的标签 (见
+/// `resolve_backup_label`)，供
+/// `list_backups` 展示历史；内容未发生变化时跳过，避免历史记录被刷屏。
+pub async fn backup_file(path: &Path, mirror_name: Option<&str>) -> Result<()> {
+    if !fs::try_exists(path).await.unwrap_or(false) {
+        return Ok(());
     }
 
-    // Sort by path string (effectively sorting by timestamp suffix)
-    backups.sort();
+    let content = fs::read(path).await?;
+    let hash = content_hash(&content);
+
+    let dir = backup_registry_dir(path);
+    fs::create_dir_all(&dir).await?;
+
+    let existing = list_backup_entries(&dir).await?;
+    if let Some(last) = existing.last() {
+        if last.content_hash == hash {
+            return Ok(());
+        }
+    }
+
+    let mut next_id = existing
+        .last()
+        .and_then(|e| e.id.parse::<u64>().ok())
+        .unwrap_or(0)
+        + 1;
+    // 正常情况下 next_id 必然是空闲的；这里的循环只是为了在极端竞争下也不会覆盖已有记录
+    while fs::try_exists(dir.join(format!("{}.meta.json", next_id)))
+        .await
+        .unwrap_or(false)
+    {
+        next_id += 1;
+    }
+
+    let timestamp = iso_timestamp(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs());
+    let entry = BackupEntry {
+        id: next_id.to_string(),
+        original_path: path.to_path_buf(),
+        timestamp,
+        mirror_name: mirror_name.map(|s| s.to_string()),
+        content_hash: hash,
+    };
+
+    fs::write(dir.join(format!("{}.bak", next_id)), &content).await?;
+    fs::write(
+        dir.join(format!("{}.meta.json", next_id)),
+        serde_json::to_string_pretty(&entry)?,
+    )
+    .await?;
+
+    println!("Backup #{} created for {:?}", next_id, path);
+    Ok(())
+}
+
+/// 列出某个文件的全部备份记录 (按时间顺序)
+pub async fn list_backups(path: &Path) -> Result<Vec<BackupEntry>> {
+    list_backup_entries(&backup_registry_dir(path)).await
+}
 
-    // Get the last one (latest timestamp)
-    let latest = backups.last().unwrap();
+/// 回滚到某条具体的备份记录
+pub async fn restore_backup(path: &Path, id: &str) -> Result<()> {
+    let dir = backup_registry_dir(path);
+    let data_path = dir.join(format!("{}.bak", id));
 
-    println!("Restoring from backup: {:?}", latest);
-    fs::copy(latest, path).await?;
-    println!("Successfully restored configuration.");
+    if !fs::try_exists(&data_path).await.unwrap_or(false) {
+        return Err(MirrorError::Custom(format!(
+            "No backup found with id '{}' for {:?}",
+            id, path
+        )));
+    }
 
+    fs::copy(&data_path, path).await?;
+    println!("Restored {:?} from backup #{}", path, id);
     Ok(())
 }
 
-/// 并发测试所有镜像源的延迟
+/// 恢复到最近的备份
+pub async fn restore_latest_backup(path: &Path) -> Result<()> {
+    let entries = list_backups(path).await?;
+    let latest = entries
+        .last()
+        .ok_or_else(|| MirrorError::Custom("No backup files found.".to_string()))?;
+
+    restore_backup(path, &latest.id).await
+}
+
+/// 并发测试所有镜像源的延迟与吞吐量
 ///
 /// 逻辑:
 /// 1. 构建带有超时设置的 HTTP Client
-/// 2. 为每个镜像源生成一个异步任务 (Task)
+/// 2. 为每个镜像源生成一个异步任务 (Task)，对 `probe_path` 发起若干次 GET 采样
 /// 3. 并行等待所有任务完成 (join_all)
 /// 4. 按延迟从小到大排序结果
-pub async fn benchmark_mirrors(mirrors: Vec<Mirror>) -> Vec<BenchmarkResult> {
-    // 构建 Client, 强制设置超时
-    let client = Client::builder()
-        .timeout(Duration::from_secs(REQUEST_TIMEOUT))
-        .build()
-        .unwrap_or_default();
+///
+/// `probe_path` 是拼接在镜像 URL 之后、保证存在的小对象路径 (见
+/// `SourceManager::probe_path`)，用来避免只测根路径时很多镜像 404 从而被
+/// 误判为超时。
+pub async fn benchmark_mirrors(mirrors: Vec<Mirror>, probe_path: &str) -> Vec<BenchmarkResult> {
+    benchmark_mirrors_with_client(&build_http_client(), mirrors, probe_path).await
+}
 
+/// 同 `benchmark_mirrors`，但由调用方提供探测用的 HTTP Client，而不是内部
+/// 固定调用 `build_http_client()`。测试可以借此传入一个指向本地 mock server
+/// 的 client，从而在不发起真实网络请求的情况下稳定地模拟不同镜像的延迟差异。
+pub async fn benchmark_mirrors_with_client(
+    client: &Client,
+    mirrors: Vec<Mirror>,
+    probe_path: &str,
+) -> Vec<BenchmarkResult> {
     let pb = ProgressBar::new(mirrors.len() as u64);
     pb.set_style(
         ProgressStyle::with_template("[{bar:40.cyan/blue}] {percent}% {msg}")
@@ -97,7 +265,7 @@ pub async fn benchmark_mirrors(mirrors: Vec<Mirror>) -> Vec<BenchmarkResult> {
         let client = client.clone();
         let pb = pb.clone();
         async move {
-            let res = check_latency(&client, m).await;
+            let res = probe_mirror(&client, m, probe_path).await;
             pb.inc(1);
             res
         }
@@ -109,49 +277,136 @@ pub async fn benchmark_mirrors(mirrors: Vec<Mirror>) -> Vec<BenchmarkResult> {
 
     pb.finish_with_message("Testing completed.");
 
-    // 排序: 延迟低的在前, 失败的(MAX)在后
-
-    results.sort_by_key(|r| r.latency_ms);
+    rank_benchmark_results(&mut results);
 
     results
 }
 
-/// 单个源测速逻辑
-async fn check_latency(client: &Client, mirror: Mirror) -> BenchmarkResult {
-    let start = Instant::now();
+/// 排序: 延迟是主要依据，按 LATENCY_TOLERANCE_MS 宽的固定区间分档 (如 0-49ms、
+/// 50-99ms)，落在同一档的镜像改用吞吐量 (从高到低) 决出名次——否则延迟只快了几毫秒、
+/// 实际带宽却差很多的镜像会仅凭那几毫秒的优势排到前面。失败的 (MAX) 延迟
+/// 天然落在最后一档。拆成独立函数是为了能在不发起真实探测的情况下单独测试
+/// 排序规则本身。
+fn rank_benchmark_results(results: &mut [BenchmarkResult]) {
+    results.sort_by(|a, b| {
+        let band = |r: &BenchmarkResult| r.latency_ms / LATENCY_TOLERANCE_MS;
+        band(a)
+            .cmp(&band(b))
+            .then_with(|| b.throughput_kbps.cmp(&a.throughput_kbps))
+            .then_with(|| a.latency_ms.cmp(&b.latency_ms))
+    });
+}
 
-    // Clean URL for benchmarking (remove cargo's "sparse+" or "git+" prefixes)
+/// 单次采样结果
+struct ProbeSample {
+    ttfb_ms: u64,
+    kbps: f64,
+}
 
-    let url_to_test = mirror
-        .url
+/// 将镜像 URL 与探测路径拼接成完整的请求地址
+fn build_probe_url(mirror_url: &str, probe_path: &str) -> String {
+    // Clean URL for benchmarking (remove cargo's "sparse+" or "git+" prefixes)
+    let base = mirror_url
         .trim_start_matches("sparse+")
         .trim_start_matches("git+");
 
-    // 使用 HEAD 请求而不是 GET，只获取元数据，速度更快且省流量
+    if probe_path.is_empty() {
+        base.to_string()
+    } else {
+        format!(
+            "{}/{}",
+            base.trim_end_matches('/'),
+            probe_path.trim_start_matches('/')
+        )
+    }
+}
+
+/// 对一个 URL 发起一次 GET 采样，记录 TTFB 与下载速率
+async fn probe_once(client: &Client, url: &str) -> Option<ProbeSample> {
+    let start = Instant::now();
 
-    // 很多镜像源根路径不一定响应，建议 URL 带有具体路径 (如 /simple)
+    let resp = client.get(url).send().await.ok()?;
+    if !resp.status().is_success() {
+        // 虽然连上了，但返回 404/500 等错误，视为不可用
+        return None;
+    }
 
-    let request = client.head(url_to_test).send();
+    // TTFB: 响应头到达的时刻
+    let ttfb_ms = start.elapsed().as_millis() as u64;
 
-    let latency_ms = match request.await {
-        Ok(resp) => {
-            if resp.status().is_success() {
-                // 计算 TTFB (Time To First Byte)
+    let bytes = resp.bytes().await.ok()?;
+    let elapsed_secs = start.elapsed().as_secs_f64().max(0.001);
+    let kbps = (bytes.len() as f64 / 1024.0) / elapsed_secs;
 
-                start.elapsed().as_millis() as u64
-            } else {
-                // 虽然连上了，但返回 404/500 等错误，视为不可用
+    Some(ProbeSample { ttfb_ms, kbps })
+}
 
-                u64::MAX
-            }
+/// 单个源测速逻辑：采样 `PROBE_SAMPLES` 次，取 TTFB 中位数作为延迟，
+/// 取各次采样吞吐量的平均值；只有全部采样都失败才判定为超时 (u64::MAX)。
+async fn probe_mirror(client: &Client, mirror: Mirror, probe_path: &str) -> BenchmarkResult {
+    let url = build_probe_url(&mirror.url, probe_path);
+
+    let mut samples = Vec::with_capacity(PROBE_SAMPLES);
+    for _ in 0..PROBE_SAMPLES {
+        if let Some(sample) = probe_once(client, &url).await {
+            samples.push(sample);
         }
+    }
 
-        Err(_) => {
-            // 连接超时、DNS 解析失败等
+    if samples.is_empty() {
+        return BenchmarkResult {
+            mirror,
+            latency_ms: u64::MAX,
+            throughput_kbps: 0,
+        };
+    }
 
-            u64::MAX
+    samples.sort_by_key(|s| s.ttfb_ms);
+    let latency_ms = samples[samples.len() / 2].ttfb_ms;
+    let throughput_kbps =
+        (samples.iter().map(|s| s.kbps).sum::<f64>() / samples.len() as f64) as u64;
+
+    BenchmarkResult {
+        mirror,
+        latency_ms,
+        throughput_kbps,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(name: &str, latency_ms: u64, throughput_kbps: u64) -> BenchmarkResult {
+        BenchmarkResult {
+            mirror: Mirror::new(name, "https://example.com"),
+            latency_ms,
+            throughput_kbps,
         }
-    };
+    }
 
-    BenchmarkResult { mirror, latency_ms }
+    #[test]
+    fn test_rank_prefers_lower_latency_when_bands_differ() {
+        let mut results = vec![result("Slow", 500, 9000), result("Fast", 20, 100)];
+        rank_benchmark_results(&mut results);
+        assert_eq!(results[0].mirror.name, "Fast");
+    }
+
+    #[test]
+    fn test_rank_uses_throughput_as_tiebreaker_within_latency_band() {
+        // Latencies are only a few ms apart (well within LATENCY_TOLERANCE_MS),
+        // so the much higher-throughput mirror should win despite being
+        // nominally a bit slower.
+        let mut results = vec![result("LowBandwidth", 40, 500), result("HighBandwidth", 45, 5000)];
+        rank_benchmark_results(&mut results);
+        assert_eq!(results[0].mirror.name, "HighBandwidth");
+    }
+
+    #[test]
+    fn test_rank_unreachable_mirrors_sort_last() {
+        let mut results = vec![result("Dead", u64::MAX, 0), result("Alive", 30, 1000)];
+        rank_benchmark_results(&mut results);
+        assert_eq!(results[0].mirror.name, "Alive");
+        assert_eq!(results[1].mirror.name, "Dead");
+    }
 }